@@ -1,11 +1,8 @@
-use channels_lite::channels::{channel_author, channel_subscriber, Network};
+use channels_lite::channels::channel_subscriber::ReceivedMessage;
+use channels_lite::channels::{channel_author, channel_subscriber, Network, Result, RetryPolicy};
 use channels_lite::utils::payload::json::PayloadBuilder;
-use failure::Fallible;
 use serde::{Deserialize, Serialize};
-use std::{
-    thread,
-    time::{Duration, SystemTime, UNIX_EPOCH},
-};
+use std::time::{SystemTime, UNIX_EPOCH};
 
 ///
 /// Some example of sensor Data
@@ -29,38 +26,38 @@ impl SensorData {
 }
 
 #[tokio::main]
-async fn main() -> Fallible<()> {
+async fn main() -> Result<()> {
     let seed_author = None;
     let seed_subscriber = Some("SOME9SUBSCRIBER9SEETKEW".to_string());
-    let delay_time: u64 = 20;
+    //Wait up to two minutes for the messages to propagate
+    let retry = RetryPolicy {
+        max_attempts: 15,
+        max_backoff_ms: 10_000,
+        deadline_ms: Some(120_000),
+        ..RetryPolicy::default()
+    };
 
     //Create Channel Instance for author
-    let mut channel_author = channel_author::Channel::new(Network::Devnet, seed_author);
+    let mut channel_author = channel_author::Channel::new(Network::Devnet, seed_author)?;
+    channel_author.set_retry_policy(retry);
 
     //Open Channel
     let (channel_address, announcement_tag) = channel_author.open().unwrap();
     println!("Author: Announced channel: {} ", channel_address);
 
-    //Give messages some time to propagate
-    println!("Waiting for propagation... ({}s)", delay_time);
-    thread::sleep(Duration::from_secs(delay_time));
-
     //Create Channel Instance for subscriber
     let mut channel_subscriber = channel_subscriber::Channel::new(
         Network::Devnet,
         channel_address,
         announcement_tag,
         seed_subscriber,
-    );
+    )?;
+    channel_subscriber.set_retry_policy(retry);
 
     //Connect to channel
     let subscription_tag = channel_subscriber.connect().unwrap();
     println!("Subscriber: Connected to channel");
 
-    //Give messages some time to propagate
-    println!("Waiting for propagation... ({}s)", delay_time);
-    thread::sleep(Duration::from_secs(delay_time));
-
     //Add subscriber
     let keyload_tag = channel_author.add_subscriber(subscription_tag).unwrap();
     println!("Author key: {}", keyload_tag);
@@ -96,32 +93,30 @@ async fn main() -> Fallible<()> {
         .unwrap();
     println!("Author: Sent tagged message");
 
-    //Give messages some time to propagate
-    println!("Waiting for propagation... ({}s)", delay_time * 2);
-    thread::sleep(Duration::from_secs(delay_time * 2));
-
     channel_subscriber.update_keyload(keyload_tag).unwrap();
     println!("Subscriber: Updated keyload");
 
     //Read all signed messages
-    let list_signed_public: Vec<(Option<String>, Option<String>)> = channel_subscriber
+    let list_signed_public: Vec<Result<ReceivedMessage>> = channel_subscriber
         .read_signed(signed_packed_tag_public)
         .unwrap();
     println!("Subscriber: Reading signed public messages");
     for msg in list_signed_public.iter() {
-        let (public, masked) = msg;
+        let msg = msg.as_ref().unwrap();
+        let (public, masked) = (&msg.public, &msg.masked);
         println!(
             "Subscriber: Found Signed Public Message -> Public: {:?} -- Masked: {:?}",
             public, masked
         )
     }
 
-    let list_signed_masked: Vec<(Option<String>, Option<String>)> = channel_subscriber
+    let list_signed_masked: Vec<Result<ReceivedMessage>> = channel_subscriber
         .read_signed(signed_packed_tag_masked)
         .unwrap();
     println!("Subscriber: Reading signed masked messages");
     for msg in list_signed_masked.iter() {
-        let (public, masked) = msg;
+        let msg = msg.as_ref().unwrap();
+        let (public, masked) = (&msg.public, &msg.masked);
         println!(
             "Subscriber: Found Signed Masked Message -> Public: {:?} -- Masked: {:?}",
             public, masked
@@ -129,33 +124,27 @@ async fn main() -> Fallible<()> {
     }
 
     //Read all tagged messages
-    let list_tagged: Vec<(Option<String>, Option<String>)> =
+    let list_tagged: Vec<Result<ReceivedMessage>> =
         channel_subscriber.read_tagged(tagged_packed_tag).unwrap();
     println!("Subscriber: Reading tagged messages");
     for msg in list_tagged.iter() {
-        let (public, masked) = msg;
+        let msg = msg.as_ref().unwrap();
+        let (public, masked) = (&msg.public, &msg.masked);
         println!(
             "Subscriber: Found Tagged Message -> Public: {:?} -- Masked: {:?}",
             public, masked
         )
     }
 
-    //Give messages some time to propagate
-    println!("Waiting for propagation... ({}s)", delay_time);
-    thread::sleep(Duration::from_secs(delay_time));
-
-    /*
     //Disconnect from channel
     let unsubscribe_tag = channel_subscriber.disconnect().unwrap();
     println!("Subscriber: Disconnected from channel");
 
-    //Give messages some time to propagate
-    println!("Waiting for propagation... ({}s)", delay_time);
-    thread::sleep(Duration::from_secs(delay_time));
-
-    channel_author.remove_subscriber(unsubscribe_tag).unwrap();
-    println!("Author: Removed subscriber");
-    */
+    let keyload_tag = channel_author
+        .remove_subscriber(unsubscribe_tag)
+        .unwrap()
+        .keyload_tag;
+    println!("Author: Removed subscriber, new key: {}", keyload_tag);
 
     Ok(())
 }
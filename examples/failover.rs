@@ -0,0 +1,59 @@
+use channels_lite::channels::transport::TangleTransport;
+use channels_lite::channels::{channel_author, channel_subscriber, NetworkConfig};
+use channels_lite::utils::mock_node::MockNode;
+use channels_lite::utils::payload::json::PayloadBuilder;
+use std::error::Error;
+
+fn main() -> Result<(), Box<dyn Error>> {
+    //Four local nodes sharing the same Tangle: one stopped, one offline, one out of sync
+    let healthy = MockNode::start()?;
+    let offline = healthy.peer()?;
+    let unsynced = healthy.peer()?;
+    let mut stopped = healthy.peer()?;
+    offline.set_online(false);
+    unsynced.set_synced(false);
+    stopped.stop();
+
+    let network = NetworkConfig {
+        nodes: vec![stopped.url(), offline.url(), unsynced.url(), healthy.url()],
+        min_weight_magnitude: 9,
+        ..NetworkConfig::default()
+    };
+
+    let mut transport = TangleTransport::new(network.clone())?;
+    for node in transport.check_health() {
+        println!(
+            "Node {} -> reachable: {}, synced: {}, latency: {:?}",
+            node.url, node.reachable, node.synced, node.latency
+        );
+    }
+
+    //Requests go to the healthy node first
+    let mut channel_author = channel_author::Channel::with_transport(transport, None)?;
+    let (channel_address, announcement_tag) = channel_author.open()?;
+    println!("Author: Announced channel: {} ", channel_address);
+
+    let mut channel_subscriber =
+        channel_subscriber::Channel::new(network, channel_address, announcement_tag, None)?;
+    let subscription_tag = channel_subscriber.connect()?;
+    println!("Subscriber: Connected to channel");
+
+    //The healthy node goes down, the requests fall back on the unsynced one
+    healthy.set_online(false);
+
+    let keyload_tag = channel_author.add_subscriber(subscription_tag)?;
+    let tagged_tag =
+        channel_author.write_tagged(PayloadBuilder::new().public(&"Still online")?.build())?;
+    println!("Author: Sent tagged message through the fallback node");
+
+    channel_subscriber.update_keyload(keyload_tag)?;
+    for msg in channel_subscriber.read_tagged(tagged_tag)? {
+        let msg = msg?;
+        println!(
+            "Subscriber: Found Tagged Message -> Public: {:?}",
+            msg.public
+        );
+    }
+
+    Ok(())
+}
@@ -0,0 +1,148 @@
+use channels_lite::channels::transport::InMemoryTransport;
+use channels_lite::channels::{channel_author, channel_subscriber, Result};
+use channels_lite::utils::payload::envelope::Schema;
+use channels_lite::utils::payload::json::{JsonSerializer, PayloadBuilder};
+use channels_lite::utils::payload::PayloadEncoding;
+use serde::{Deserialize, Serialize};
+
+///
+/// Some example of sensor Data
+///
+#[derive(Serialize, Debug, Deserialize)]
+pub struct SensorData {
+    ts: u64,
+    presure: f32,
+}
+
+fn main() -> Result<()> {
+    //Both channels share the same in-memory Tangle, no node nor propagation delay involved
+    let tangle = InMemoryTransport::new();
+
+    let mut channel_author = channel_author::Channel::with_transport(tangle.clone(), None)?;
+    let (channel_address, announcement_tag) = channel_author.open().unwrap();
+    println!("Author: Announced channel: {} ", channel_address);
+
+    let mut channel_subscriber = channel_subscriber::Channel::with_transport(
+        tangle.clone(),
+        channel_address,
+        announcement_tag,
+        None,
+    )?;
+    let subscription_tag = channel_subscriber.connect().unwrap();
+    println!("Subscriber: Connected to channel");
+
+    let keyload_tag = channel_author.add_subscriber(subscription_tag).unwrap();
+    println!("Author key: {}", keyload_tag);
+
+    let signed_tag = channel_author
+        .write_signed(
+            true,
+            PayloadBuilder::new()
+                .public(&SensorData {
+                    ts: 1,
+                    presure: 1.0,
+                })?
+                .masked(&SensorData {
+                    ts: 1,
+                    presure: 2.0,
+                })?
+                .build(),
+        )
+        .unwrap();
+    let tagged_tag = channel_author
+        .write_tagged(
+            PayloadBuilder::new()
+                .public(&SensorData {
+                    ts: 2,
+                    presure: 3.0,
+                })?
+                .masked(&SensorData {
+                    ts: 2,
+                    presure: 4.0,
+                })?
+                .build(),
+        )
+        .unwrap();
+    println!("Author: Sent {} messages", tangle.len());
+
+    channel_subscriber.update_keyload(keyload_tag).unwrap();
+
+    for msg in channel_subscriber.read_signed(signed_tag).unwrap() {
+        let msg = msg?;
+        println!(
+            "Subscriber: Found Signed Message signed by {:?} at {:?} -> Public: {:?} -- Masked: {:?}",
+            msg.signer, msg.timestamp, msg.public, msg.masked
+        );
+    }
+    for msg in channel_subscriber
+        .read_tagged_as::<JsonSerializer, SensorData, SensorData>(tagged_tag)
+        .unwrap()
+    {
+        let msg = msg?;
+        println!(
+            "Subscriber: Found Tagged Message -> Public: {:?} -- Masked: {:?}",
+            msg.public, msg.masked
+        );
+    }
+
+    //New messages can also be discovered without knowing their tag, this one uses the compact binary encoding
+    channel_author
+        .write_signed(
+            false,
+            PayloadBuilder::with_encoding(PayloadEncoding::Binary)
+                .public(&SensorData {
+                    ts: 3,
+                    presure: 5.0,
+                })?
+                .build(),
+        )
+        .unwrap();
+    for msg in channel_subscriber.fetch_next_messages().unwrap() {
+        let msg = msg?;
+        println!(
+            "Subscriber: Fetched {:?} Message {} -> Public: {:?}",
+            msg.message_type, msg.msgid, msg.public
+        );
+    }
+
+    //Enveloped payloads tell the subscriber their content type and schema
+    let enveloped_tag = channel_author
+        .write_tagged(
+            PayloadBuilder::with_schema(Schema::new("sensor-data", 1))
+                .masked(&SensorData {
+                    ts: 4,
+                    presure: 6.0,
+                })?
+                .build(),
+        )
+        .unwrap();
+    for msg in channel_subscriber
+        .read_tagged_envelopes(enveloped_tag)
+        .unwrap()
+    {
+        if let Some(envelope) = msg?.masked {
+            let data: SensorData = envelope.decode()?;
+            println!(
+                "Subscriber: Found {} Envelope with schema {:?} -> Masked: {:?}",
+                envelope.header.content_type, envelope.header.schema, data
+            );
+        }
+    }
+
+    //Blobs too large for a message are split across several packets
+    let blob: Vec<u8> = (0..4096).map(|i| (i % 251) as u8).collect();
+    let chunk_tags = channel_author.write_tagged_chunked(&blob)?;
+    println!(
+        "Author: Sent a {} bytes blob in {} chunks",
+        blob.len(),
+        chunk_tags.len()
+    );
+    let received = channel_subscriber.read_chunked(&chunk_tags)?;
+    println!(
+        "Subscriber: Reassembled a {} bytes blob, identical: {}",
+        received.len(),
+        received == blob
+    );
+
+    Ok(())
+}
@@ -0,0 +1,75 @@
+use channels_lite::channels::{channel_author, channel_subscriber, NetworkConfig, PowMode};
+use channels_lite::utils::mock_node::MockNode;
+use channels_lite::utils::payload::json::PayloadBuilder;
+use serde::{Deserialize, Serialize};
+use std::error::Error;
+
+///
+/// Some example of sensor Data
+///
+#[derive(Serialize, Debug, Deserialize)]
+pub struct SensorData {
+    ts: u64,
+    presure: f32,
+}
+
+fn main() -> Result<(), Box<dyn Error>> {
+    //Local node, messages go through the iota client and its HTTP API, the PoW is done here
+    let node = MockNode::start()?;
+    let network = NetworkConfig {
+        pow: PowMode::Local,
+        pow_threads: Some(2),
+        ..NetworkConfig::custom(node.url(), 9)
+    };
+
+    let mut channel_author = channel_author::Channel::new(network.clone(), None)?;
+    let (channel_address, announcement_tag) = channel_author.open().unwrap();
+    println!("Author: Announced channel: {} ", channel_address);
+
+    let mut channel_subscriber =
+        channel_subscriber::Channel::new(network, channel_address, announcement_tag, None)?;
+    let subscription_tag = channel_subscriber.connect().unwrap();
+    println!("Subscriber: Connected to channel");
+
+    let keyload_tag = channel_author.add_subscriber(subscription_tag).unwrap();
+
+    let tagged_tag = channel_author
+        .write_tagged(
+            PayloadBuilder::new()
+                .public(&SensorData {
+                    ts: 1,
+                    presure: 1.0,
+                })?
+                .masked(&SensorData {
+                    ts: 1,
+                    presure: 2.0,
+                })?
+                .build(),
+        )
+        .unwrap();
+    println!(
+        "Author: Sent tagged message, node stores {} transactions",
+        node.transactions_count()
+    );
+    for report in channel_author.take_send_reports() {
+        println!(
+            "Author: Sent {} to {}, local PoW (MWM {}) took {:?}, the whole send {:?}",
+            report.msgid,
+            report.node,
+            report.min_weight_magnitude,
+            report.pow_duration,
+            report.duration
+        );
+    }
+
+    channel_subscriber.update_keyload(keyload_tag).unwrap();
+    for msg in channel_subscriber.read_tagged(tagged_tag).unwrap() {
+        let msg = msg?;
+        println!(
+            "Subscriber: Found Tagged Message -> Public: {:?} -- Masked: {:?}",
+            msg.public, msg.masked
+        );
+    }
+
+    Ok(())
+}
@@ -0,0 +1,133 @@
+use channels_lite::channels::transport::{ChannelTransport, InMemoryTransport};
+use channels_lite::channels::{
+    channel_author, channel_subscriber, ChannelError, Result, RetryPolicy,
+};
+use channels_lite::utils::payload::json::PayloadBuilder;
+use iota_streams::app_channels::api::tangle::{Address, Message};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::thread;
+use std::time::Duration;
+
+///
+/// In-memory Tangle that can be switched offline, like a field device
+/// losing its connectivity
+///
+struct FlakyTransport {
+    tangle: InMemoryTransport,
+    online: Arc<AtomicBool>,
+}
+
+impl ChannelTransport for FlakyTransport {
+    fn send_message(&mut self, message: &Message) -> Result<()> {
+        if !self.online.load(Ordering::SeqCst) {
+            return Err(ChannelError::Transport("No connectivity".to_string()));
+        }
+        self.tangle.send_message(message)
+    }
+
+    fn recv_messages(&mut self, link: &Address) -> Result<Vec<Message>> {
+        self.tangle.recv_messages(link)
+    }
+}
+
+fn main() -> Result<()> {
+    let tangle = InMemoryTransport::new();
+    let online = Arc::new(AtomicBool::new(true));
+
+    let mut channel_author = channel_author::Channel::with_transport(tangle.clone(), None)?;
+    let (channel_address, announcement_tag) = channel_author.open()?;
+
+    let mut channel_subscriber = channel_subscriber::Channel::with_transport(
+        tangle.clone(),
+        channel_address,
+        announcement_tag,
+        None,
+    )?;
+    let subscription_tag = channel_subscriber.connect()?;
+    channel_author.add_subscriber(subscription_tag)?;
+    println!("Subscriber: Connected to channel");
+
+    //From now on every message goes through the queue file
+    let queue_path = std::env::temp_dir().join("channels_lite_outbound_queue.json");
+    let _ = std::fs::remove_file(&queue_path);
+    channel_author.set_retry_policy(RetryPolicy {
+        initial_backoff_ms: 100,
+        max_backoff_ms: 500,
+        ..RetryPolicy::default()
+    });
+    channel_author.enable_outbound_queue(
+        &queue_path,
+        FlakyTransport {
+            tangle: tangle.clone(),
+            online: online.clone(),
+        },
+    )?;
+
+    //Connectivity is lost, the messages wait in the queue
+    online.store(false, Ordering::SeqCst);
+    let mut handles = Vec::new();
+    for presure in 1..=3 {
+        let handle = channel_author.enqueue_tagged(
+            PayloadBuilder::new()
+                .public(&format!("presure {}", presure))?
+                .build(),
+        )?;
+        println!("Author: Enqueued tagged message {}", handle.tag());
+        handles.push(handle);
+    }
+    thread::sleep(Duration::from_millis(300));
+    println!(
+        "Author: {} messages waiting in {}",
+        channel_author.pending_messages(),
+        queue_path.display()
+    );
+
+    //Connectivity is back, the queue is flushed in order
+    online.store(true, Ordering::SeqCst);
+    for handle in handles {
+        println!("Author: Sent tagged message {}", handle.wait()?);
+    }
+
+    //The device restarts with a message still queued and an older snapshot
+    let snapshot = channel_author.export_state("password")?;
+    online.store(false, Ordering::SeqCst);
+    let handle =
+        channel_author.enqueue_tagged(PayloadBuilder::new().public(&"presure 4")?.build())?;
+    println!(
+        "Author: Enqueued tagged message {} before restarting",
+        handle.tag()
+    );
+    drop(channel_author);
+    online.store(true, Ordering::SeqCst);
+
+    //The queue file holds the newer operations, the new keyload is sent after the queued message
+    let mut channel_author = channel_author::Channel::import_state_with_queue(
+        tangle.clone(),
+        &snapshot,
+        "password",
+        &queue_path,
+        FlakyTransport {
+            tangle: tangle.clone(),
+            online: online.clone(),
+        },
+    )?;
+    let keyloads = channel_author.reshare_keyloads()?;
+    while channel_author.pending_messages() > 0 {
+        thread::sleep(Duration::from_millis(100));
+    }
+    println!(
+        "Author: Restored the channel, new keyload {}",
+        keyloads.keyload_tag
+    );
+
+    for msg in channel_subscriber.fetch_next_messages()? {
+        let msg = msg?;
+        println!(
+            "Subscriber: Found Tagged Message -> Public: {:?}",
+            msg.public
+        );
+    }
+
+    Ok(())
+}
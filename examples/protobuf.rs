@@ -0,0 +1,71 @@
+use channels_lite::channels::transport::InMemoryTransport;
+use channels_lite::channels::{channel_author, channel_subscriber, Result};
+use channels_lite::utils::payload::protobuf::{PayloadBuilder, ProtobufSerializer};
+use channels_lite::utils::payload::PayloadEncoding;
+
+///
+/// Some example of sensor Data, usually generated by prost-build from:
+///
+/// ```proto
+/// message SensorData {
+///     uint64 ts = 1;
+///     float presure = 2;
+/// }
+/// ```
+///
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct SensorData {
+    #[prost(uint64, tag = "1")]
+    pub ts: u64,
+    #[prost(float, tag = "2")]
+    pub presure: f32,
+}
+
+fn main() -> Result<()> {
+    let tangle = InMemoryTransport::new();
+
+    let mut channel_author = channel_author::Channel::with_transport(tangle.clone(), None)?;
+    let (channel_address, announcement_tag) = channel_author.open().unwrap();
+    println!("Author: Announced channel: {} ", channel_address);
+
+    let mut channel_subscriber = channel_subscriber::Channel::with_transport(
+        tangle.clone(),
+        channel_address,
+        announcement_tag,
+        None,
+    )?;
+    let subscription_tag = channel_subscriber.connect().unwrap();
+    let keyload_tag = channel_author.add_subscriber(subscription_tag).unwrap();
+    channel_subscriber.update_keyload(keyload_tag).unwrap();
+    println!("Subscriber: Connected to channel");
+
+    //Protobuf is a binary format, the binary encoding keeps it compact
+    let tagged_tag = channel_author
+        .write_tagged(
+            PayloadBuilder::with_encoding(PayloadEncoding::Binary)
+                .public(&SensorData {
+                    ts: 1,
+                    presure: 1.0,
+                })?
+                .masked(&SensorData {
+                    ts: 1,
+                    presure: 2.0,
+                })?
+                .build(),
+        )
+        .unwrap();
+    println!("Author: Sent tagged message {}", tagged_tag);
+
+    for msg in channel_subscriber
+        .read_tagged_as::<ProtobufSerializer, SensorData, SensorData>(tagged_tag)
+        .unwrap()
+    {
+        let msg = msg?;
+        println!(
+            "Subscriber: Found Tagged Message -> Public: {:?} -- Masked: {:?}",
+            msg.public, msg.masked
+        );
+    }
+
+    Ok(())
+}
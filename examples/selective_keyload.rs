@@ -0,0 +1,79 @@
+use channels_lite::channels::transport::InMemoryTransport;
+use channels_lite::channels::{channel_author, channel_subscriber, Result};
+use channels_lite::utils::payload::json::PayloadBuilder;
+
+fn main() -> Result<()> {
+    let tangle = InMemoryTransport::new();
+
+    let mut channel_author = channel_author::Channel::with_transport(tangle.clone(), None)?;
+    let (channel_address, announcement_tag) = channel_author.open().unwrap();
+    println!("Author: Announced channel: {} ", channel_address);
+
+    let mut crew = channel_subscriber::Channel::with_transport(
+        tangle.clone(),
+        channel_address.clone(),
+        announcement_tag.clone(),
+        None,
+    )?;
+    let mut customer = channel_subscriber::Channel::with_transport(
+        tangle.clone(),
+        channel_address,
+        announcement_tag,
+        None,
+    )?;
+
+    let crew_subscription = crew.connect().unwrap();
+    channel_author.add_subscriber(crew_subscription.clone())?;
+    let customer_subscription = customer.connect().unwrap();
+    let keyload_tag = channel_author.add_subscriber(customer_subscription.clone())?;
+    println!("Author: Shared keyload {} for everyone", keyload_tag);
+
+    //A second keyload only the maintenance crew can open
+    let crew_id = channel_author.subscriber_id(&crew_subscription).unwrap();
+    let crew_keyload_tag = channel_author.share_keyload_for(&[crew_id])?;
+    println!("Author: Shared keyload {} for the crew", crew_keyload_tag);
+
+    let public_tag = channel_author.write_tagged(
+        PayloadBuilder::new()
+            .masked(&"Service resumes at 10:00")?
+            .build(),
+    )?;
+    let crew_tag = channel_author.write_tagged_with_keyload(
+        &crew_keyload_tag,
+        PayloadBuilder::new()
+            .masked(&"Replace pump 3, access code 4711")?
+            .build(),
+    )?;
+
+    customer.update_keyload(keyload_tag)?;
+    for msg in customer.read_tagged(public_tag)? {
+        let msg = msg?;
+        println!("Customer: Found Tagged Message -> Masked: {:?}", msg.masked);
+    }
+    match customer.update_keyload(crew_keyload_tag.clone()) {
+        Ok(()) => println!("Customer: Unexpectedly opened the crew keyload"),
+        Err(e) => println!("Customer: Can not open the crew keyload: {}", e),
+    }
+
+    crew.update_keyload(crew_keyload_tag.clone())?;
+    for msg in crew.read_tagged(crew_tag)? {
+        let msg = msg?;
+        println!("Crew: Found Tagged Message -> Masked: {:?}", msg.masked);
+    }
+
+    //Revoking the customer leaves the crew keyload as is, the ones it was in are shared again
+    let customer_id = channel_author
+        .subscriber_id(&customer_subscription)
+        .unwrap();
+    let revocation = channel_author.revoke_subscriber(&customer_id)?;
+    println!(
+        "Author: Revoked the customer, new keyload {}, shared again {:?}",
+        revocation.keyload_tag, revocation.selective_keyloads
+    );
+    channel_author.write_tagged_with_keyload(
+        &crew_keyload_tag,
+        PayloadBuilder::new().masked(&"Pump 3 replaced")?.build(),
+    )?;
+
+    Ok(())
+}
@@ -0,0 +1,19 @@
+//!
+//! Run a mock IRI/Hornet node
+//!
+//! Usage: `cargo run --features mock-node --bin mock_node -- [address]`
+//!
+use channels_lite::utils::mock_node::MockNode;
+use std::{env, thread};
+
+fn main() {
+    let address = env::args()
+        .nth(1)
+        .unwrap_or_else(|| "127.0.0.1:14265".to_string());
+    let _node = MockNode::bind(&address).expect("Failed to start the mock node");
+    println!("Mock node listening on http://{}", address);
+
+    loop {
+        thread::park();
+    }
+}
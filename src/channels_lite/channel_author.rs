@@ -1,53 +1,330 @@
 //!
 //! Channel author
 //!
-use super::Network;
-use crate::utils::{payload::PacketPayload, random_seed};
-use anyhow::{bail, Result};
-use iota::client as iota_client;
-use iota_streams::app::transport::tangle::{
-    client::{RecvOptions, SendTrytesOptions},
-    PAYLOAD_BYTES,
-};
-use iota_streams::app::transport::Transport;
+use super::queue::{OutboundQueue, QueuedMessage};
+use super::state::{self, StoredMessage};
+use super::transport::{ChannelTransport, SendReport, TangleTransport};
+use super::{run_blocking, ChannelError, NetworkConfig, Result, RetryPolicy, SubscriberId};
+use crate::utils::payload::{self, PacketPayload};
+use crate::utils::{chunked, random_seed};
+use iota_streams::app::transport::tangle::PAYLOAD_BYTES;
 use iota_streams::app_channels::{
-    api::tangle::{Address, Author},
+    api::tangle::{Address, Author, Message},
     message,
 };
+use iota_streams::core::psk;
+use iota_streams::core_edsig::key_exchange::ntru;
+use iota_streams::ddml::types::Bytes;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::Path;
 use std::string::ToString;
 
+///
+/// Operation applied to the Author, kept to rebuild its state
+///
+/// Operations are recorded before sending, so that a failed send never lets a
+/// one-time signing key be used twice after a restore. Runs of packets linked
+/// to the same message are kept as a single operation with a count, so the
+/// log grows with the keyloads and subscriptions, not with the packets.
+///
+#[derive(Serialize, Deserialize, Clone)]
+enum Operation {
+    Announce,
+    Subscribe {
+        subscriber: SubscriberId,
+        subscription_tag: String,
+        message: StoredMessage,
+    },
+    Keyload {
+        link_to: String,
+    },
+    SelectiveKeyload {
+        link_to: String,
+        subscribers: Vec<SubscriberId>,
+    },
+    Revoke {
+        subscriber: SubscriberId,
+    },
+    Signed {
+        link_to: String,
+        #[serde(default = "one")]
+        count: u64,
+    },
+    Tagged {
+        link_to: String,
+        #[serde(default = "one")]
+        count: u64,
+    },
+}
+
+fn one() -> u64 {
+    1
+}
+
+///
+/// Number of messages the operations wrapped, the longer log is the newer
+///
+fn log_length(operations: &[Operation]) -> u64 {
+    operations
+        .iter()
+        .map(|operation| match operation {
+            Operation::Signed { count, .. } | Operation::Tagged { count, .. } => *count,
+            Operation::Revoke { .. } => 0,
+            _ => 1,
+        })
+        .sum()
+}
+
+///
+/// Persisted author state
+///
+/// The Author's sequence and key state is restored by replaying the
+/// operations, starting from the seed.
+///
+#[derive(Serialize, Deserialize)]
+struct AuthorState {
+    seed: String,
+    announcement_id: String,
+    last_keyload_tag: String,
+    operations: Vec<Operation>,
+}
+
+///
+/// Author operations checkpointed in the outbound queue file with every
+/// message, the seed is never written there
+///
+#[derive(Serialize, Deserialize)]
+struct AuthorLog {
+    channel_address: String,
+    operations: Vec<Operation>,
+}
+
+///
+/// Keyloads shared again, without a revoked subscriber or after a restore
+///
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SharedKeyloads {
+    /// Tag of the new keyload for every remaining subscriber
+    ///
+    pub keyload_tag: String,
+    /// Keyloads shared with `share_keyload_for` that had to be shared again,
+    /// as the old tag and the tag of the keyload shared again for the
+    /// remaining subscribers, if any is left. The old tags can not be written
+    /// to anymore.
+    ///
+    pub selective_keyloads: Vec<(String, Option<String>)>,
+}
+
 ///
 /// Channel
 ///
-pub struct Channel {
+pub struct Channel<T = TangleTransport> {
     author: Author,
-    send_opt: SendTrytesOptions,
+    transport: T,
+    seed: String,
     channel_address: String,
     announcement_id: String,
     last_keyload_tag: String,
+    operations: Vec<Operation>,
+    subscriber_keys: HashMap<SubscriberId, ntru::Pkid>,
+    selective_keyloads: HashMap<String, Vec<SubscriberId>>,
+    needs_keyload: bool,
+    lost_keyloads: Vec<(String, Vec<SubscriberId>)>,
+    retry: RetryPolicy,
+    queue: Option<OutboundQueue>,
 }
 
-impl Channel {
+impl Channel<TangleTransport> {
     ///
     /// Initialize the Channel
     ///
-    pub fn new(node: Network, seed_option: Option<String>) -> Channel {
+    pub fn new<N: Into<NetworkConfig>>(node: N, seed_option: Option<String>) -> Result<Channel> {
+        let transport = TangleTransport::new(node)?;
+        Channel::with_transport(transport, seed_option)
+    }
+
+    ///
+    /// Restore a Channel exported with `export_state`
+    ///
+    pub fn import_state<N: Into<NetworkConfig>>(
+        node: N,
+        bytes: &[u8],
+        password: &str,
+    ) -> Result<Channel> {
+        let transport = TangleTransport::new(node)?;
+        Channel::import_state_with_transport(transport, bytes, password)
+    }
+}
+
+impl<T> Channel<T>
+where
+    T: ChannelTransport,
+{
+    ///
+    /// Initialize the Channel on top of a custom transport
+    ///
+    pub fn with_transport(transport: T, seed_option: Option<String>) -> Result<Channel<T>> {
         let seed = match seed_option {
             Some(seed) => seed,
             None => random_seed::new(),
         };
         let author = Author::new(&seed, "utf-8", PAYLOAD_BYTES, false);
-        iota_client::Client::add_node(node.as_string()).unwrap();
 
-        let channel_address = author.channel_address().unwrap().to_string();
+        let channel_address = author
+            .channel_address()
+            .ok_or_else(|| ChannelError::Streams("Author has no channel address".to_string()))?
+            .to_string();
 
-        Self {
+        Ok(Self {
             author: author,
-            send_opt: node.send_options(),
+            transport: transport,
+            seed: seed,
             channel_address: channel_address,
             announcement_id: String::default(),
             last_keyload_tag: String::default(),
+            operations: Vec::new(),
+            subscriber_keys: HashMap::new(),
+            selective_keyloads: HashMap::new(),
+            needs_keyload: false,
+            lost_keyloads: Vec::new(),
+            retry: RetryPolicy::default(),
+            queue: None,
+        })
+    }
+
+    ///
+    /// Restore a Channel exported with `export_state` on top of a custom transport
+    ///
+    /// Nothing is sent, the channel is restored offline. Keyload session keys
+    /// can not be derived from the seed, so masked packets can not be written
+    /// until `reshare_keyloads()` shares new keyloads, see `needs_keyload()`.
+    ///
+    /// Use `import_state_with_queue` for a channel that had its outbound
+    /// queue enabled.
+    ///
+    pub fn import_state_with_transport(
+        transport: T,
+        bytes: &[u8],
+        password: &str,
+    ) -> Result<Channel<T>> {
+        Channel::restore(transport, bytes, password, None)
+    }
+
+    ///
+    /// Restore a Channel exported with `export_state` and resume its outbound
+    /// queue
+    ///
+    /// The queue file holds the author operations up to the last message
+    /// enqueued, they are replayed when newer than the exported state, so
+    /// that no message is ever wrapped again in the slot of a queued one. As
+    /// with `import_state_with_transport`, the keyloads have to be shared
+    /// again with `reshare_keyloads()`, they then go through the queue.
+    ///
+    pub fn import_state_with_queue<P, Q>(
+        transport: T,
+        bytes: &[u8],
+        password: &str,
+        path: P,
+        queue_transport: Q,
+    ) -> Result<Channel<T>>
+    where
+        P: AsRef<Path>,
+        Q: ChannelTransport + Send + 'static,
+    {
+        let log = Self::queued_log(path.as_ref())?;
+        let mut channel = Channel::restore(transport, bytes, password, log)?;
+        channel.enable_outbound_queue(path, queue_transport)?;
+        Ok(channel)
+    }
+
+    ///
+    /// Export the channel state, encrypted with the given password
+    ///
+    pub fn export_state(&self, password: &str) -> Result<Vec<u8>> {
+        state::seal(
+            &AuthorState {
+                seed: self.seed.clone(),
+                announcement_id: self.announcement_id.clone(),
+                last_keyload_tag: self.last_keyload_tag.clone(),
+                operations: self.operations.clone(),
+            },
+            password,
+        )
+    }
+
+    ///
+    /// Return true when the channel was restored and its keyloads have not
+    /// been shared again yet, masked packets can not be written until then
+    ///
+    pub fn needs_keyload(&self) -> bool {
+        self.needs_keyload || !self.lost_keyloads.is_empty()
+    }
+
+    ///
+    /// Keyloads shared with `share_keyload_for` before the export, lost on
+    /// restore, with their subscribers. `reshare_keyloads()` shares them again.
+    ///
+    pub fn lost_keyloads(&self) -> Vec<(String, Vec<SubscriberId>)> {
+        self.lost_keyloads.clone()
+    }
+
+    ///
+    /// Share the keyloads of a restored channel again: a keyload for every
+    /// subscriber, and the lost selective ones for their remaining subscribers
+    ///
+    /// The subscribers have to update their keyloads with the new tags.
+    ///
+    pub fn reshare_keyloads(&mut self) -> Result<SharedKeyloads> {
+        let announcement_link = self.link(&self.announcement_id)?;
+        self.last_keyload_tag = self.send_keyload(&announcement_link)?;
+        self.needs_keyload = false;
+
+        let mut selective_keyloads = Vec::new();
+        while !self.lost_keyloads.is_empty() {
+            let (keyload_tag, subscribers) = self.lost_keyloads[0].clone();
+            let subscribers: Vec<SubscriberId> = subscribers
+                .into_iter()
+                .filter(|subscriber| self.subscriber_keys.contains_key(subscriber))
+                .collect();
+            let reshared = if subscribers.is_empty() {
+                None
+            } else {
+                Some(self.share_keyload_for(&subscribers)?)
+            };
+            self.lost_keyloads.remove(0);
+            selective_keyloads.push((keyload_tag, reshared));
         }
+
+        Ok(SharedKeyloads {
+            keyload_tag: self.last_keyload_tag.clone(),
+            selective_keyloads: selective_keyloads,
+        })
+    }
+
+    ///
+    /// Tag of the last keyload shared with the subscribers
+    ///
+    pub fn keyload_tag(&self) -> Option<String> {
+        if self.last_keyload_tag == String::default() {
+            None
+        } else {
+            Some(self.last_keyload_tag.clone())
+        }
+    }
+
+    ///
+    /// Retry policy of the sends and of the lookups of subscriptions
+    ///
+    pub fn retry_policy(&self) -> RetryPolicy {
+        self.retry
+    }
+
+    ///
+    /// Set the retry policy
+    ///
+    pub fn set_retry_policy(&mut self, retry: RetryPolicy) {
+        self.retry = retry;
     }
 
     ///
@@ -55,8 +332,8 @@ impl Channel {
     ///
     pub fn open(&mut self) -> Result<(String, String)> {
         let announcement_message = self.author.announce()?;
-        iota_client::Client::get()
-            .send_message_with_options(&announcement_message, self.send_opt)?;
+        self.record(Operation::Announce);
+        self.send(&announcement_message)?;
 
         self.announcement_id = announcement_message.link.msgid.to_string();
 
@@ -67,34 +344,40 @@ impl Channel {
     /// Add subscriber
     ///
     pub fn add_subscriber(&mut self, subscribe_tag: String) -> Result<String> {
-        let subscribe_link = match Address::from_str(&self.channel_address, &subscribe_tag) {
-            Ok(subscribe_link) => subscribe_link,
-            Err(()) => bail!(
-                "Failed to create Address from {}:{}",
-                &self.channel_address,
-                &subscribe_tag
-            ),
-        };
+        let subscribe_link = self.link(&subscribe_tag)?;
 
-        let message_list = iota_client::Client::get()
-            .recv_messages_with_options(&subscribe_link, RecvOptions::default())?;
-        for tx in message_list.iter() {
-            let header = tx.parse_header()?;
-            if header.check_content_type(message::SUBSCRIBE) {
-                match self.author.unwrap_subscribe(header.clone()) {
-                    Ok(_) => {
-                        break;
+        let retry = self.retry;
+        retry.run(|| {
+            let mut subscribed = Err(ChannelError::MessageNotFound(format!(
+                "No subscribe message at {}",
+                subscribe_tag
+            )));
+            let message_list = self.transport.recv_messages(&subscribe_link)?;
+            for tx in message_list.iter() {
+                let header = tx.parse_header()?;
+                if header.check_content_type(message::SUBSCRIBE) {
+                    match self.author.unwrap_subscribe(header.clone()) {
+                        Ok(public_key) => {
+                            self.subscriber_keys.insert(
+                                SubscriberId::from(&public_key),
+                                public_key.get_pkid().clone(),
+                            );
+                            self.record(Operation::Subscribe {
+                                subscriber: SubscriberId::from(&public_key),
+                                subscription_tag: subscribe_tag.clone(),
+                                message: StoredMessage::from(tx),
+                            });
+                            subscribed = Ok(());
+                            break;
+                        }
+                        Err(e) => subscribed = Err(e.into()),
                     }
-                    Err(e) => println!("Subscribe Packet Error: {}", e),
                 }
             }
-        }
+            subscribed
+        })?;
 
-        self.last_keyload_tag = {
-            let keyload = self.author.share_keyload_for_everyone(&subscribe_link)?;
-            iota_client::Client::get().send_message_with_options(&keyload.0, self.send_opt)?;
-            keyload.0.link.msgid.to_string()
-        };
+        self.last_keyload_tag = self.send_keyload(&subscribe_link)?;
 
         Ok(self.last_keyload_tag.clone())
     }
@@ -102,86 +385,789 @@ impl Channel {
     ///
     /// Write signed packet
     ///
-    pub fn write_signed<T>(&mut self, masked: bool, payload: T) -> Result<String>
+    pub fn write_signed<P>(&mut self, masked: bool, payload: P) -> Result<String>
+    where
+        P: PacketPayload,
+    {
+        let signed_packet = self.sign_packet(masked, None, payload)?;
+        self.send(&signed_packet)?;
+        Ok(signed_packet.link.msgid.to_string())
+    }
+
+    ///
+    /// Write signed packet, masked for the subscribers of the given keyload
+    ///
+    pub fn write_signed_with_keyload<P>(&mut self, keyload_tag: &str, payload: P) -> Result<String>
+    where
+        P: PacketPayload,
+    {
+        let signed_packet = self.sign_packet(true, Some(keyload_tag), payload)?;
+        self.send(&signed_packet)?;
+        Ok(signed_packet.link.msgid.to_string())
+    }
+
+    ///
+    /// Write tagged packet
+    ///
+    pub fn write_tagged<P>(&mut self, payload: P) -> Result<String>
+    where
+        P: PacketPayload,
+    {
+        let tagged_packet = self.tag_packet(None, payload)?;
+        self.send(&tagged_packet)?;
+        Ok(tagged_packet.link.msgid.to_string())
+    }
+
+    ///
+    /// Write tagged packet, for the subscribers of the given keyload
+    ///
+    pub fn write_tagged_with_keyload<P>(&mut self, keyload_tag: &str, payload: P) -> Result<String>
     where
-        T: PacketPayload,
+        P: PacketPayload,
     {
-        let signed_packet_link = {
-            if masked {
-                if self.last_keyload_tag.clone() == String::default() {
-                    panic!("Can not send masked message before a keyload is generated and shared")
+        let tagged_packet = self.tag_packet(Some(keyload_tag), payload)?;
+        self.send(&tagged_packet)?;
+        Ok(tagged_packet.link.msgid.to_string())
+    }
+
+    ///
+    /// Write a blob too large for a single message as several signed
+    /// packets, returns their tags in order
+    ///
+    /// The blob is split into chunks, put in the masked data when `masked`,
+    /// that the subscriber reassembles with `read_chunked`. Every chunk is
+    /// built before the first one is sent. When a chunk can not be sent
+    /// after others were, `ChannelError::PartialWrite` holds the tags of the
+    /// chunks already written.
+    ///
+    pub fn write_signed_chunked(&mut self, masked: bool, blob: &[u8]) -> Result<Vec<String>> {
+        let payloads = chunked::payloads(blob, masked)?;
+        self.write_chunks(payloads, |channel, payload| {
+            channel.write_signed(masked, payload)
+        })
+    }
+
+    ///
+    /// Write a blob too large for a single message as several tagged
+    /// packets, returns their tags in order
+    ///
+    /// Chunks not all written are reported as in `write_signed_chunked`.
+    ///
+    pub fn write_tagged_chunked(&mut self, blob: &[u8]) -> Result<Vec<String>> {
+        let payloads = chunked::payloads(blob, true)?;
+        self.write_chunks(payloads, |channel, payload| channel.write_tagged(payload))
+    }
+
+    ///
+    /// Send the messages through a durable outbound queue
+    ///
+    /// Messages are kept in the file until sent, in order, by a background
+    /// thread using its own transport (e.g. a second `TangleTransport` on the
+    /// same network). The author operations are written with every message,
+    /// in the same atomic update. Messages left by a previous run are sent
+    /// first: after a restart, restore the channel with
+    /// `import_state_with_queue`. A queue written by a newer state of the
+    /// channel than this one is refused.
+    ///
+    /// Once enabled, every message of the channel goes through the queue.
+    ///
+    pub fn enable_outbound_queue<P, Q>(&mut self, path: P, transport: Q) -> Result<()>
+    where
+        P: AsRef<Path>,
+        Q: ChannelTransport + Send + 'static,
+    {
+        if let Some(log) = Self::queued_log(path.as_ref())? {
+            if log.channel_address != self.channel_address {
+                return Err(ChannelError::Queue(format!(
+                    "{} belongs to channel {}",
+                    path.as_ref().display(),
+                    log.channel_address
+                )));
+            }
+            if log_length(&log.operations) > log_length(&self.operations) {
+                return Err(ChannelError::InvalidState(format!(
+                    "{} was written by a newer state of the channel, restore it with import_state_with_queue",
+                    path.as_ref().display()
+                )));
+            }
+        }
+        self.queue = Some(OutboundQueue::open(
+            path,
+            transport,
+            self.retry,
+            self.author_log()?,
+        )?);
+        Ok(())
+    }
+
+    ///
+    /// Number of messages waiting in the outbound queue
+    ///
+    pub fn pending_messages(&self) -> usize {
+        self.queue.as_ref().map_or(0, |queue| queue.len())
+    }
+
+    ///
+    /// Enqueue a signed packet, the returned handle resolves once it is sent
+    ///
+    pub fn enqueue_signed<P>(&mut self, masked: bool, payload: P) -> Result<QueuedMessage>
+    where
+        P: PacketPayload,
+    {
+        self.queue()?;
+        let signed_packet = self.sign_packet(masked, None, payload)?;
+        self.queue()?.push(&signed_packet, self.author_log()?)
+    }
+
+    ///
+    /// Enqueue a tagged packet, the returned handle resolves once it is sent
+    ///
+    pub fn enqueue_tagged<P>(&mut self, payload: P) -> Result<QueuedMessage>
+    where
+        P: PacketPayload,
+    {
+        self.queue()?;
+        let tagged_packet = self.tag_packet(None, payload)?;
+        self.queue()?.push(&tagged_packet, self.author_log()?)
+    }
+
+    ///
+    /// Reports of the messages sent since the last call (node, PoW mode and
+    /// times), keyloads and queued messages included, when the transports
+    /// keep them
+    ///
+    pub fn take_send_reports(&mut self) -> Vec<SendReport> {
+        let mut reports = self.transport.take_send_reports();
+        if let Some(queue) = &self.queue {
+            reports.extend(queue.take_send_reports());
+        }
+        reports
+    }
+
+    ///
+    /// Known subscribers
+    ///
+    pub fn subscribers(&self) -> Vec<SubscriberId> {
+        let mut subscribers: Vec<SubscriberId> = Vec::new();
+        for operation in self.operations.iter() {
+            if let Operation::Subscribe { subscriber, .. } = operation {
+                if self.subscriber_keys.contains_key(subscriber)
+                    && !subscribers.contains(subscriber)
+                {
+                    subscribers.push(subscriber.clone());
                 }
-                let keyload_link =
-                    Address::from_str(&self.channel_address, &self.last_keyload_tag).unwrap();
-                let msg = self.author.sign_packet(
-                    &keyload_link,
-                    &payload.public_data(),
-                    &payload.masked_data(),
-                )?;
-                let ret_link = msg.0;
-                iota_client::Client::get().send_message_with_options(&ret_link, self.send_opt)?;
-                ret_link.link.clone()
+            }
+        }
+        subscribers
+    }
+
+    ///
+    /// Identifier of the subscriber that sent the given subscription
+    ///
+    pub fn subscriber_id(&self, subscription_tag: &str) -> Option<SubscriberId> {
+        self.operations
+            .iter()
+            .find_map(|operation| match operation {
+                Operation::Subscribe {
+                    subscriber,
+                    subscription_tag: tag,
+                    ..
+                } if tag == subscription_tag => Some(subscriber.clone()),
+                _ => None,
+            })
+    }
+
+    ///
+    /// Share a keyload for the given subscribers only and return its tag
+    ///
+    /// The packets written with `write_signed_with_keyload` or
+    /// `write_tagged_with_keyload` and this tag are masked for these
+    /// subscribers, who update their keyload with the tag. The last keyload
+    /// shared for everyone is left as is. Subscribers listed twice are kept
+    /// once, an empty list fails with `ChannelError::NoSubscriber`.
+    ///
+    pub fn share_keyload_for(&mut self, subscribers: &[SubscriberId]) -> Result<String> {
+        if let Some(unknown) = subscribers
+            .iter()
+            .find(|subscriber| !self.subscriber_keys.contains_key(subscriber))
+        {
+            return Err(ChannelError::UnknownSubscriber(unknown.to_string()));
+        }
+        let mut unique: Vec<SubscriberId> = Vec::with_capacity(subscribers.len());
+        for subscriber in subscribers.iter() {
+            if !unique.contains(subscriber) {
+                unique.push(subscriber.clone());
+            }
+        }
+        if unique.is_empty() {
+            return Err(ChannelError::NoSubscriber);
+        }
+        let subscribers = &unique[..];
+
+        let announcement_link = self.link(&self.announcement_id)?;
+        let keyload = self.author.share_keyload(
+            &announcement_link,
+            &psk::PskIds::new(),
+            &self.pkids(subscribers),
+        )?;
+        self.record(Operation::SelectiveKeyload {
+            link_to: self.announcement_id.clone(),
+            subscribers: subscribers.to_vec(),
+        });
+        self.send(&keyload.0)?;
+
+        let keyload_tag = keyload.0.link.msgid.to_string();
+        self.selective_keyloads
+            .insert(keyload_tag.clone(), subscribers.to_vec());
+        Ok(keyload_tag)
+    }
+
+    ///
+    /// Subscribers of a keyload shared with `share_keyload_for`
+    ///
+    pub fn keyload_subscribers(&self, keyload_tag: &str) -> Option<Vec<SubscriberId>> {
+        self.selective_keyloads.get(keyload_tag).cloned()
+    }
+
+    ///
+    /// Remove subscriber
+    ///
+    /// Process the unsubscribe message and share new keyloads without the
+    /// subscriber, see `revoke_subscriber`
+    ///
+    pub fn remove_subscriber(&mut self, unsubscribe_tag: String) -> Result<SharedKeyloads> {
+        let unsubscribe_link = self.link(&unsubscribe_tag)?;
+
+        let retry = self.retry;
+        let subscriber_id = retry.run(|| {
+            let mut unsubscribed = Err(ChannelError::MessageNotFound(format!(
+                "No unsubscribe message at {}",
+                unsubscribe_tag
+            )));
+            let message_list = self.transport.recv_messages(&unsubscribe_link)?;
+            for tx in message_list.iter() {
+                let header = tx.parse_header()?;
+                if header.check_content_type(message::UNSUBSCRIBE) {
+                    match self.author.unwrap_unsubscribe(header.clone()) {
+                        Ok(public_key) => {
+                            unsubscribed = Ok(SubscriberId::from(&public_key));
+                            break;
+                        }
+                        Err(e) => unsubscribed = Err(e.into()),
+                    }
+                }
+            }
+            unsubscribed
+        })?;
+        self.revoke_subscriber(&subscriber_id)
+    }
+
+    ///
+    /// Revoke the access of a subscriber
+    ///
+    /// Share a new keyload without the subscriber, and share again without it
+    /// the keyloads of `share_keyload_for` it was in. Returns the new tags,
+    /// the subscribers have to update their keyloads with them. The Author
+    /// state is kept, nothing is signed again.
+    ///
+    pub fn revoke_subscriber(&mut self, subscriber_id: &SubscriberId) -> Result<SharedKeyloads> {
+        if self.subscriber_keys.remove(subscriber_id).is_none() {
+            return Err(ChannelError::UnknownSubscriber(subscriber_id.to_string()));
+        }
+        self.record(Operation::Revoke {
+            subscriber: subscriber_id.clone(),
+        });
+
+        let announcement_link = self.link(&self.announcement_id)?;
+        self.last_keyload_tag = self.send_keyload(&announcement_link)?;
+
+        // The revoked subscriber still holds the session keys of its keyloads
+        let mut revoked: Vec<(String, Vec<SubscriberId>)> = self
+            .selective_keyloads
+            .iter()
+            .filter(|(_, subscribers)| subscribers.contains(subscriber_id))
+            .map(|(keyload_tag, subscribers)| {
+                (
+                    keyload_tag.clone(),
+                    subscribers
+                        .iter()
+                        .filter(|subscriber| self.subscriber_keys.contains_key(subscriber))
+                        .cloned()
+                        .collect(),
+                )
+            })
+            .collect();
+        revoked.sort_by(|a, b| a.0.cmp(&b.0));
+
+        let mut selective_keyloads = Vec::new();
+        for (keyload_tag, subscribers) in revoked {
+            self.selective_keyloads.remove(&keyload_tag);
+            let reshared = if subscribers.is_empty() {
+                None
             } else {
-                let msg = self.author.sign_packet(
-                    &Address::from_str(&self.channel_address, &self.announcement_id).unwrap(),
-                    &payload.public_data(),
-                    &payload.masked_data(),
-                )?;
-                let ret_link = msg.0;
-                iota_client::Client::get().send_message_with_options(&ret_link, self.send_opt)?;
-                ret_link.link.clone()
+                Some(self.share_keyload_for(&subscribers)?)
+            };
+            selective_keyloads.push((keyload_tag, reshared));
+        }
+
+        Ok(SharedKeyloads {
+            keyload_tag: self.last_keyload_tag.clone(),
+            selective_keyloads: selective_keyloads,
+        })
+    }
+
+    ///
+    /// Share a keyload for every subscriber and return its tag
+    ///
+    /// The Author can not forget a key, so the subscribers are listed
+    /// explicitly to leave the revoked ones out.
+    ///
+    fn send_keyload(&mut self, link_to: &Address) -> Result<String> {
+        let pkids = self.subscriber_keys.values().cloned().collect();
+        let keyload = self
+            .author
+            .share_keyload(link_to, &psk::PskIds::new(), &pkids)?;
+        self.record(Operation::Keyload {
+            link_to: link_to.msgid.to_string(),
+        });
+        self.send(&keyload.0)?;
+        Ok(keyload.0.link.msgid.to_string())
+    }
+
+    ///
+    /// Public key ids of the subscribers, the unknown ones are left out
+    ///
+    fn pkids(&self, subscribers: &[SubscriberId]) -> Vec<ntru::Pkid> {
+        subscribers
+            .iter()
+            .filter_map(|subscriber| self.subscriber_keys.get(subscriber).cloned())
+            .collect()
+    }
+
+    ///
+    /// Tag of the keyload to link a masked packet to, the given one or the
+    /// last one shared for everyone
+    ///
+    fn keyload_link(&self, keyload_tag: Option<&str>) -> Result<String> {
+        match keyload_tag {
+            Some(tag)
+                if tag == self.last_keyload_tag || self.selective_keyloads.contains_key(tag) =>
+            {
+                Ok(tag.to_string())
             }
+            Some(tag) => Err(ChannelError::InvalidTag(tag.to_string())),
+            None if self.last_keyload_tag == String::default() => Err(ChannelError::NoKeyload),
+            None => Ok(self.last_keyload_tag.clone()),
+        }
+    }
+
+    ///
+    /// Sign a packet, linked to the keyload when masked
+    ///
+    fn sign_packet<P: PacketPayload>(
+        &mut self,
+        masked: bool,
+        keyload_tag: Option<&str>,
+        payload: P,
+    ) -> Result<Message> {
+        payload::check_size(&payload)?;
+        let link_to = if masked {
+            self.keyload_link(keyload_tag)?
+        } else {
+            self.announcement_id.clone()
         };
+        let msg = self.author.sign_packet(
+            &self.link(&link_to)?,
+            &payload.public_data(),
+            &payload.masked_data(),
+        )?;
+        self.record(Operation::Signed {
+            link_to: link_to,
+            count: 1,
+        });
+        Ok(msg.0)
+    }
 
-        Ok(signed_packet_link.msgid.to_string())
+    ///
+    /// Tag a packet, linked to the keyload
+    ///
+    fn tag_packet<P: PacketPayload>(
+        &mut self,
+        keyload_tag: Option<&str>,
+        payload: P,
+    ) -> Result<Message> {
+        payload::check_size(&payload)?;
+        let link_to = self.keyload_link(keyload_tag)?;
+        let msg = self.author.tag_packet(
+            &self.link(&link_to)?,
+            &payload.public_data(),
+            &payload.masked_data(),
+        )?;
+        self.record(Operation::Tagged {
+            link_to: link_to,
+            count: 1,
+        });
+        Ok(msg.0)
     }
 
     ///
-    /// Write tagged packet
+    /// Send a message, following the retry policy, or enqueue it when the
+    /// outbound queue is enabled
+    ///
+    fn send(&mut self, message: &Message) -> Result<()> {
+        if let Some(queue) = self.queue.as_ref() {
+            return queue.push(message, self.author_log()?).map(|_| ());
+        }
+        let retry = self.retry;
+        retry.run(|| self.transport.send_message(message))
+    }
+
+    ///
+    /// Append an operation to the log, counting it in the last one when it
+    /// is a packet linked to the same message
+    ///
+    fn record(&mut self, operation: Operation) {
+        match (self.operations.last_mut(), &operation) {
+            (
+                Some(Operation::Signed { link_to, count }),
+                Operation::Signed {
+                    link_to: next_link_to,
+                    count: next_count,
+                },
+            )
+            | (
+                Some(Operation::Tagged { link_to, count }),
+                Operation::Tagged {
+                    link_to: next_link_to,
+                    count: next_count,
+                },
+            ) if link_to == next_link_to => *count += next_count,
+            _ => self.operations.push(operation),
+        }
+    }
+
+    fn queue(&self) -> Result<&OutboundQueue> {
+        self.queue
+            .as_ref()
+            .ok_or_else(|| ChannelError::Queue("Outbound queue not enabled".to_string()))
+    }
+
+    ///
+    /// Write the chunks in order, stopping at the first one that fails
     ///
-    pub fn write_tagged<T>(&mut self, payload: T) -> Result<String>
+    fn write_chunks<P, F>(&mut self, payloads: Vec<P>, write: F) -> Result<Vec<String>>
     where
-        T: PacketPayload,
+        F: Fn(&mut Self, P) -> Result<String>,
     {
-        let keyload_link =
-            Address::from_str(&self.channel_address, &self.last_keyload_tag).unwrap();
-
-        let tagged_packet_link = {
-            let msg = self.author.tag_packet(
-                &keyload_link,
-                &payload.public_data(),
-                &payload.masked_data(),
-            )?;
-            let ret_link = msg.0;
-            iota_client::Client::get().send_message_with_options(&ret_link, self.send_opt)?;
-            ret_link.link.clone()
+        let mut tags = Vec::with_capacity(payloads.len());
+        for payload in payloads {
+            match write(self, payload) {
+                Ok(tag) => tags.push(tag),
+                Err(e) if tags.is_empty() => return Err(e),
+                Err(e) => {
+                    return Err(ChannelError::PartialWrite {
+                        tags: tags,
+                        reason: Box::new(e),
+                    })
+                }
+            }
+        }
+        Ok(tags)
+    }
+
+    fn author_log(&self) -> Result<serde_json::Value> {
+        serde_json::to_value(&AuthorLog {
+            channel_address: self.channel_address.clone(),
+            operations: self.operations.clone(),
+        })
+        .map_err(|e| ChannelError::Queue(e.to_string()))
+    }
+
+    ///
+    /// Author operations checkpointed in the queue file, if any
+    ///
+    fn queued_log(path: &Path) -> Result<Option<AuthorLog>> {
+        OutboundQueue::checkpoint(path)?
+            .map(|checkpoint| {
+                serde_json::from_value(checkpoint)
+                    .map_err(|e| ChannelError::Queue(format!("{}: {}", path.display(), e)))
+            })
+            .transpose()
+    }
+
+    ///
+    /// Rebuild the Author of an exported state, from the operations of the
+    /// queue file when they are newer
+    ///
+    fn restore(
+        transport: T,
+        bytes: &[u8],
+        password: &str,
+        log: Option<AuthorLog>,
+    ) -> Result<Channel<T>> {
+        let saved: AuthorState = state::unseal(bytes, password)?;
+
+        let mut channel = Channel::with_transport(transport, Some(saved.seed))?;
+        let operations = match log {
+            Some(log) if log.channel_address != channel.channel_address => {
+                return Err(ChannelError::Queue(format!(
+                    "The outbound queue belongs to channel {}",
+                    log.channel_address
+                )));
+            }
+            Some(log) if log_length(&log.operations) > log_length(&saved.operations) => {
+                log.operations
+            }
+            _ => saved.operations,
         };
+        channel.needs_keyload = operations
+            .iter()
+            .any(|operation| matches!(operation, Operation::Keyload { .. }));
+        channel.lost_keyloads = channel.rebuild(operations)?;
 
-        Ok(tagged_packet_link.msgid.to_string())
+        if channel.announcement_id != saved.announcement_id {
+            return Err(ChannelError::InvalidState(format!(
+                "Restored announcement {} does not match {}",
+                channel.announcement_id, saved.announcement_id
+            )));
+        }
+        Ok(channel)
     }
 
-    /*
     ///
-    /// Remove subscriber
+    /// Recreate the Author from the seed and replay the operations
     ///
+    /// Only used on import: every signed packet is signed again to move the
+    /// one-time key index, and the session keys of the keyloads shared
+    /// before are lost. Returns the keyloads shared with `share_keyload_for`
+    /// that were still in use, sorted by tag, they are left out of the
+    /// channel until shared again.
     ///
-    pub fn remove_subscriber(&mut self, unsubscribe_tag: String) -> Result<()> {
-        let unsubscribe_link = Address::from_str(&self.channel_address, &unsubscribe_tag).unwrap();
+    fn rebuild(&mut self, operations: Vec<Operation>) -> Result<Vec<(String, Vec<SubscriberId>)>> {
+        self.author = Author::new(&self.seed, "utf-8", PAYLOAD_BYTES, false);
+        self.subscriber_keys.clear();
+        self.selective_keyloads.clear();
+        for operation in operations.iter() {
+            self.replay(operation)?;
+        }
+        self.operations = operations;
 
-        let message_list = iota_client::Client::get()
-            .recv_messages_with_options(&unsubscribe_link, RecvOptions::default())?;
-        for tx in message_list.iter() {
-            let header = tx.parse_header()?;
-            if header.check_content_type(message::UNSUBSCRIBE) {
-                match self.author.unsubscribe(header.clone()) {
-                    Ok(_) => {
-                        break;
-                    }
-                    Err(e) => println!("Unsubscribe Packet Error: {}", e),
+        let mut lost: Vec<(String, Vec<SubscriberId>)> = self.selective_keyloads.drain().collect();
+        lost.sort_by(|a, b| a.0.cmp(&b.0));
+        Ok(lost)
+    }
+
+    ///
+    /// Apply an operation to the Author without sending anything
+    ///
+    fn replay(&mut self, operation: &Operation) -> Result<()> {
+        match operation {
+            Operation::Announce => {
+                let announcement_message = self.author.announce()?;
+                self.announcement_id = announcement_message.link.msgid.to_string();
+            }
+            Operation::Subscribe { message, .. } => {
+                let header = message.to_message()?.parse_header()?;
+                let public_key = self.author.unwrap_subscribe(header)?;
+                self.subscriber_keys.insert(
+                    SubscriberId::from(&public_key),
+                    public_key.get_pkid().clone(),
+                );
+            }
+            Operation::Keyload { link_to } => {
+                let pkids = self.subscriber_keys.values().cloned().collect();
+                self.author
+                    .share_keyload(&self.link(link_to)?, &psk::PskIds::new(), &pkids)?;
+            }
+            Operation::SelectiveKeyload {
+                link_to,
+                subscribers,
+            } => {
+                let keyload = self.author.share_keyload(
+                    &self.link(link_to)?,
+                    &psk::PskIds::new(),
+                    &self.pkids(subscribers),
+                )?;
+                self.selective_keyloads
+                    .insert(keyload.0.link.msgid.to_string(), subscribers.clone());
+            }
+            Operation::Revoke { subscriber } => {
+                // The keyloads the subscriber was in were shared again after it
+                self.subscriber_keys.remove(subscriber);
+                self.selective_keyloads
+                    .retain(|_, subscribers| !subscribers.contains(subscriber));
+            }
+            Operation::Signed { link_to, count } => {
+                let link = self.link(link_to)?;
+                for _ in 0..*count {
+                    self.author
+                        .sign_packet(&link, &Bytes::default(), &Bytes::default())?;
+                }
+            }
+            Operation::Tagged { link_to, count } => {
+                let link = self.link(link_to)?;
+                for _ in 0..*count {
+                    self.author
+                        .tag_packet(&link, &Bytes::default(), &Bytes::default())?;
                 }
             }
         }
         Ok(())
     }
-    */
+
+    fn link(&self, tag: &str) -> Result<Address> {
+        Address::from_str(&self.channel_address, tag)
+            .map_err(|()| ChannelError::InvalidTag(tag.to_string()))
+    }
+}
+
+///
+/// Channel author with async methods
+///
+pub type AsyncChannel<T = TangleTransport> = super::AsyncChannel<Channel<T>>;
+
+impl<T> super::AsyncChannel<Channel<T>>
+where
+    T: ChannelTransport + Send + 'static,
+{
+    ///
+    /// Open a channel
+    ///
+    pub async fn open(&self) -> Result<(String, String)> {
+        run_blocking(&self.channel, |channel| channel.open()).await
+    }
+
+    ///
+    /// Add subscriber
+    ///
+    pub async fn add_subscriber(&self, subscribe_tag: String) -> Result<String> {
+        run_blocking(&self.channel, move |channel| {
+            channel.add_subscriber(subscribe_tag)
+        })
+        .await
+    }
+
+    ///
+    /// Write signed packet
+    ///
+    pub async fn write_signed<P>(&self, masked: bool, payload: P) -> Result<String>
+    where
+        P: PacketPayload + Send + 'static,
+    {
+        run_blocking(&self.channel, move |channel| {
+            channel.write_signed(masked, payload)
+        })
+        .await
+    }
+
+    ///
+    /// Write tagged packet
+    ///
+    pub async fn write_tagged<P>(&self, payload: P) -> Result<String>
+    where
+        P: PacketPayload + Send + 'static,
+    {
+        run_blocking(&self.channel, move |channel| channel.write_tagged(payload)).await
+    }
+
+    ///
+    /// Write a blob as several signed packets
+    ///
+    pub async fn write_signed_chunked(&self, masked: bool, blob: Vec<u8>) -> Result<Vec<String>> {
+        run_blocking(&self.channel, move |channel| {
+            channel.write_signed_chunked(masked, &blob)
+        })
+        .await
+    }
+
+    ///
+    /// Write a blob as several tagged packets
+    ///
+    pub async fn write_tagged_chunked(&self, blob: Vec<u8>) -> Result<Vec<String>> {
+        run_blocking(&self.channel, move |channel| {
+            channel.write_tagged_chunked(&blob)
+        })
+        .await
+    }
+
+    ///
+    /// Write signed packet, masked for the subscribers of the given keyload
+    ///
+    pub async fn write_signed_with_keyload<P>(
+        &self,
+        keyload_tag: String,
+        payload: P,
+    ) -> Result<String>
+    where
+        P: PacketPayload + Send + 'static,
+    {
+        run_blocking(&self.channel, move |channel| {
+            channel.write_signed_with_keyload(&keyload_tag, payload)
+        })
+        .await
+    }
+
+    ///
+    /// Write tagged packet, for the subscribers of the given keyload
+    ///
+    pub async fn write_tagged_with_keyload<P>(
+        &self,
+        keyload_tag: String,
+        payload: P,
+    ) -> Result<String>
+    where
+        P: PacketPayload + Send + 'static,
+    {
+        run_blocking(&self.channel, move |channel| {
+            channel.write_tagged_with_keyload(&keyload_tag, payload)
+        })
+        .await
+    }
+
+    ///
+    /// Share a keyload for the given subscribers only
+    ///
+    pub async fn share_keyload_for(&self, subscribers: Vec<SubscriberId>) -> Result<String> {
+        run_blocking(&self.channel, move |channel| {
+            channel.share_keyload_for(&subscribers)
+        })
+        .await
+    }
+
+    ///
+    /// Remove a subscriber from its unsubscribe message
+    ///
+    pub async fn remove_subscriber(&self, unsubscribe_tag: String) -> Result<SharedKeyloads> {
+        run_blocking(&self.channel, move |channel| {
+            channel.remove_subscriber(unsubscribe_tag)
+        })
+        .await
+    }
+
+    ///
+    /// Revoke a subscriber
+    ///
+    pub async fn revoke_subscriber(&self, subscriber_id: SubscriberId) -> Result<SharedKeyloads> {
+        run_blocking(&self.channel, move |channel| {
+            channel.revoke_subscriber(&subscriber_id)
+        })
+        .await
+    }
+
+    ///
+    /// Share the keyloads of a restored channel again
+    ///
+    pub async fn reshare_keyloads(&self) -> Result<SharedKeyloads> {
+        run_blocking(&self.channel, |channel| channel.reshare_keyloads()).await
+    }
+
+    ///
+    /// Export the channel state, encrypted with the password
+    ///
+    pub async fn export_state(&self, password: String) -> Result<Vec<u8>> {
+        run_blocking(&self.channel, move |channel| {
+            channel.export_state(&password)
+        })
+        .await
+    }
 }
@@ -1,216 +1,927 @@
 //!
 //! Channel Subscriber
 //!
-use super::Network;
-use crate::utils::{payload::json::Payload, random_seed};
-use iota::client as iota_client;
-use iota_streams::app::transport::tangle::{
-    client::{RecvOptions, SendTrytesOptions},
-    PAYLOAD_BYTES,
-};
-use iota_streams::app::transport::Transport;
+use super::state::{self, StoredMessage};
+use super::transport::{ChannelTransport, SendReport, TangleTransport};
+use super::{run_blocking, ChannelError, NetworkConfig, Result, RetryPolicy};
+use crate::utils::chunked::ChunkAssembler;
+use crate::utils::payload::envelope::{Envelope, PayloadFormat};
+use crate::utils::payload::{self, json::Payload, PayloadDeserializer};
+use crate::utils::random_seed;
+use futures::stream::{self, Stream};
+use iota_streams::app::transport::tangle::PAYLOAD_BYTES;
 use iota_streams::app_channels::{
-    api::tangle::{Address, Subscriber},
+    api::tangle::{Address, Message, Subscriber},
     message,
 };
+use iota_streams::ddml::types::Bytes;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashSet, VecDeque};
+use std::time::Duration;
+
+///
+/// Type of a received message
+///
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MessageType {
+    /// Signed packet
+    ///
+    Signed,
+    /// Tagged packet
+    ///
+    Tagged,
+}
+
+///
+/// Message read from the channel
+///
+#[derive(Clone, Debug)]
+pub struct ReceivedMessage<P = String, M = String> {
+    /// Message tag
+    ///
+    pub msgid: String,
+    /// Type of the message
+    ///
+    pub message_type: MessageType,
+    /// Public key of the signer, for signed packets
+    ///
+    pub signer: Option<String>,
+    /// Tag of the message this one is linked to
+    ///
+    pub linked_to: String,
+    /// Attachment timestamp in milliseconds, if reported by the transport
+    ///
+    pub timestamp: Option<i64>,
+    /// Public data
+    ///
+    pub public: Option<P>,
+    /// Masked data
+    ///
+    pub masked: Option<M>,
+}
 
-use anyhow::Result;
+impl<P, M> ReceivedMessage<P, M> {
+    ///
+    /// Convert the public and masked data, keeping the metadata
+    ///
+    fn try_map<Q, N, FP, FM>(self, public: FP, masked: FM) -> Result<ReceivedMessage<Q, N>>
+    where
+        FP: FnOnce(P) -> Result<Option<Q>>,
+        FM: FnOnce(M) -> Result<Option<N>>,
+    {
+        Ok(ReceivedMessage {
+            msgid: self.msgid,
+            message_type: self.message_type,
+            signer: self.signer,
+            linked_to: self.linked_to,
+            timestamp: self.timestamp,
+            public: match self.public {
+                Some(data) => public(data)?,
+                None => None,
+            },
+            masked: match self.masked {
+                Some(data) => masked(data)?,
+                None => None,
+            },
+        })
+    }
+}
+
+///
+/// Persisted subscriber state
+///
+/// The announcement and the last keyload are kept as received, so that the
+/// subscriber can be rebuilt without fetching them again.
+///
+#[derive(Serialize, Deserialize)]
+struct SubscriberState {
+    seed: String,
+    channel_address: String,
+    announcement_tag: String,
+    subscription_tag: String,
+    is_connected: bool,
+    announcement: Option<StoredMessage>,
+    keyload: Option<StoredMessage>,
+    #[serde(default)]
+    cursor: Option<String>,
+}
 
 ///
 /// Channel subscriber
 ///
-pub struct Channel {
+pub struct Channel<T = TangleTransport> {
     subscriber: Subscriber,
     is_connected: bool,
-    send_opt: SendTrytesOptions,
+    transport: T,
     announcement_link: Address,
     subscription_link: Address,
     channel_address: String,
+    seed: String,
+    announcement: Option<StoredMessage>,
+    keyload: Option<StoredMessage>,
+    cursor: Option<String>,
+    replay_to: Option<String>,
+    retry: RetryPolicy,
 }
 
-impl Channel {
+impl Channel<TangleTransport> {
     ///
     /// Initialize the subscriber
     ///
-    pub fn new(
-        node: Network,
+    pub fn new<N: Into<NetworkConfig>>(
+        node: N,
+        channel_address: String,
+        announcement_tag: String,
+        seed_option: Option<String>,
+    ) -> Result<Channel> {
+        let transport = TangleTransport::new(node)?;
+        Channel::with_transport(transport, channel_address, announcement_tag, seed_option)
+    }
+
+    ///
+    /// Restore a subscriber exported with `export_state`
+    ///
+    pub fn import_state<N: Into<NetworkConfig>>(
+        node: N,
+        bytes: &[u8],
+        password: &str,
+    ) -> Result<Channel> {
+        let transport = TangleTransport::new(node)?;
+        Channel::import_state_with_transport(transport, bytes, password)
+    }
+}
+
+impl<T> Channel<T>
+where
+    T: ChannelTransport,
+{
+    ///
+    /// Initialize the subscriber on top of a custom transport
+    ///
+    pub fn with_transport(
+        transport: T,
         channel_address: String,
         announcement_tag: String,
         seed_option: Option<String>,
-    ) -> Channel {
+    ) -> Result<Channel<T>> {
+        let announcement_link = Address::from_str(&channel_address, &announcement_tag)
+            .map_err(|()| ChannelError::InvalidTag(announcement_tag.clone()))?;
         let seed = match seed_option {
             Some(seed) => seed,
             None => random_seed::new(),
         };
         let subscriber = Subscriber::new(&seed, "utf-8", PAYLOAD_BYTES);
-        iota_client::Client::add_node(node.as_string()).unwrap();
 
-        Self {
+        Ok(Self {
             subscriber: subscriber,
             is_connected: false,
-            send_opt: node.send_options(),
-            announcement_link: Address::from_str(&channel_address, &announcement_tag).unwrap(),
+            transport: transport,
+            announcement_link: announcement_link,
             subscription_link: Address::default(),
             channel_address: channel_address,
-        }
+            seed: seed,
+            announcement: None,
+            keyload: None,
+            cursor: None,
+            replay_to: None,
+            retry: RetryPolicy::default(),
+        })
     }
 
     ///
-    /// Connect
+    /// Restore a subscriber exported with `export_state` on top of a custom transport
     ///
-    pub fn connect(&mut self) -> Result<String> {
-        let message_list = iota_client::Client::get()
-            .recv_messages_with_options(&self.announcement_link, RecvOptions::default())?;
+    /// Nothing is sent nor received: the subscription is rebuilt locally and
+    /// the last keyload is applied again. The messages already returned by
+    /// `fetch_next_messages` are unwrapped again by its next call, to move the
+    /// sequencing state, but are not returned twice.
+    ///
+    pub fn import_state_with_transport(
+        transport: T,
+        bytes: &[u8],
+        password: &str,
+    ) -> Result<Channel<T>> {
+        let saved: SubscriberState = state::unseal(bytes, password)?;
 
-        let mut found_valid_msg = false;
+        let mut channel = Channel::with_transport(
+            transport,
+            saved.channel_address,
+            saved.announcement_tag,
+            Some(saved.seed),
+        )?;
 
-        for tx in message_list.iter() {
-            let header = tx.parse_header()?;
-            if header.check_content_type(message::ANNOUNCE) {
-                self.subscriber.unwrap_announcement(header.clone())?;
-                found_valid_msg = true;
-                break;
+        if let Some(announcement) = saved.announcement {
+            let header = announcement.to_message()?.parse_header()?;
+            channel.subscriber.unwrap_announcement(header)?;
+            channel.announcement = Some(announcement);
+        }
+
+        if saved.is_connected {
+            let msg = channel.subscriber.subscribe(&channel.announcement_link)?;
+            if msg.link.msgid.to_string() != saved.subscription_tag {
+                return Err(ChannelError::InvalidState(format!(
+                    "Restored subscription {} does not match {}",
+                    msg.link.msgid, saved.subscription_tag
+                )));
             }
+            channel.subscription_link = msg.link.clone();
+            channel.is_connected = true;
         }
-        if found_valid_msg {
-            let subscribe_link = {
-                let msg = self.subscriber.subscribe(&self.announcement_link)?;
-                iota_client::Client::get().send_message_with_options(&msg, self.send_opt)?;
-                msg.link.clone()
-            };
-
-            self.subscription_link = subscribe_link;
-            self.is_connected = true;
-        } else {
-            println!("No valid announce message found");
+
+        if let Some(keyload) = saved.keyload {
+            let header = keyload.to_message()?.parse_header()?;
+            channel.subscriber.unwrap_keyload(header)?;
+            channel.keyload = Some(keyload);
         }
+
+        channel.cursor = saved.cursor.clone();
+        channel.replay_to = saved.cursor;
+        Ok(channel)
+    }
+
+    ///
+    /// Export the subscriber state, encrypted with the given password
+    ///
+    pub fn export_state(&self, password: &str) -> Result<Vec<u8>> {
+        state::seal(
+            &SubscriberState {
+                seed: self.seed.clone(),
+                channel_address: self.channel_address.clone(),
+                announcement_tag: self.announcement_link.msgid.to_string(),
+                subscription_tag: self.subscription_link.msgid.to_string(),
+                is_connected: self.is_connected,
+                announcement: self.announcement.clone(),
+                keyload: self.keyload.clone(),
+                cursor: self.cursor.clone(),
+            },
+            password,
+        )
+    }
+
+    ///
+    /// Retry policy of the sends and of the announcement and keyload lookups
+    ///
+    pub fn retry_policy(&self) -> RetryPolicy {
+        self.retry
+    }
+
+    ///
+    /// Set the retry policy
+    ///
+    pub fn set_retry_policy(&mut self, retry: RetryPolicy) {
+        self.retry = retry;
+    }
+
+    ///
+    /// Connect
+    ///
+    /// Wait for the announcement to appear, following the retry policy
+    ///
+    pub fn connect(&mut self) -> Result<String> {
+        let retry = self.retry;
+        retry.run(|| {
+            let message_list = self.transport.recv_messages(&self.announcement_link)?;
+            for tx in message_list.iter() {
+                let header = tx.parse_header()?;
+                if header.check_content_type(message::ANNOUNCE) {
+                    self.subscriber.unwrap_announcement(header.clone())?;
+                    self.announcement = Some(StoredMessage::from(tx));
+                    return Ok(());
+                }
+            }
+            Err(ChannelError::AnnouncementNotFound)
+        })?;
+
+        let subscribe_link = {
+            let msg = self.subscriber.subscribe(&self.announcement_link)?;
+            self.send(&msg)?;
+            msg.link.clone()
+        };
+
+        self.subscription_link = subscribe_link;
+        self.is_connected = true;
         Ok(self.subscription_link.msgid.to_string())
     }
 
-    /*
+    ///
+    /// Reports of the messages sent since the last call (node, PoW mode and
+    /// times), subscribe and unsubscribe messages, when the transport keeps
+    /// them
+    ///
+    pub fn take_send_reports(&mut self) -> Vec<SendReport> {
+        self.transport.take_send_reports()
+    }
+
     ///
     /// Disconnect
     ///
+    /// Publish the unsubscribe message and return its tag, the channel can
+    /// not be read anymore afterwards
+    ///
     pub fn disconnect(&mut self) -> Result<String> {
+        if !self.is_connected {
+            return Err(ChannelError::NotConnected);
+        }
         let unsubscribe_link = {
             let msg = self.subscriber.unsubscribe(&self.subscription_link)?;
-            iota_client::Client::get().send_message_with_options(&msg, self.send_opt)?;
+            self.send(&msg)?;
             msg.link.msgid
         };
+        self.is_connected = false;
         Ok(unsubscribe_link.to_string())
-    }*/
+    }
 
     ///
     /// Read signed packet
     ///
+    /// Each message is decoded on its own, failures are reported per message.
+    ///
     pub fn read_signed(
         &mut self,
         signed_packet_tag: String,
-    ) -> Result<Vec<(Option<String>, Option<String>)>> {
-        let mut response: Vec<(Option<String>, Option<String>)> = Vec::new();
-
-        if self.is_connected {
-            let link = Address::from_str(&self.channel_address, &signed_packet_tag).unwrap();
-            let message_list = iota_client::Client::get()
-                .recv_messages_with_options(&link, RecvOptions::default())?;
+    ) -> Result<Vec<Result<ReceivedMessage>>> {
+        let packets = self.unwrap_packets(&signed_packet_tag, Some(MessageType::Signed))?;
+        Ok(Self::decode_strings(packets))
+    }
 
-            for tx in message_list.iter() {
-                let header = tx.parse_header()?;
-                if header.check_content_type(message::SIGNED_PACKET) {
-                    match self.subscriber.unwrap_signed_packet(header.clone()) {
-                        Ok((_signer, unwrapped_public, unwrapped_masked)) => {
-                            response.push((
-                                Payload::unwrap_data(
-                                    &String::from_utf8(unwrapped_public.0).unwrap(),
-                                )
-                                .unwrap(),
-                                Payload::unwrap_data(
-                                    &String::from_utf8(unwrapped_masked.0).unwrap(),
-                                )
-                                .unwrap(),
-                            ));
-                        }
-                        Err(e) => println!("Signed Packet Error: {}", e),
-                    }
-                }
-            }
-        } else {
-            println!("Channel not connected");
-        }
+    ///
+    /// Read signed packet and deserialize the public and masked data
+    ///
+    /// Each message is decoded on its own, failures are reported per message.
+    ///
+    pub fn read_signed_as<S, P, M>(
+        &mut self,
+        signed_packet_tag: String,
+    ) -> Result<Vec<Result<ReceivedMessage<P, M>>>>
+    where
+        S: PayloadDeserializer<P> + PayloadDeserializer<M> + PayloadFormat,
+    {
+        let packets = self.unwrap_packets(&signed_packet_tag, Some(MessageType::Signed))?;
+        Ok(Self::decode_as::<S, P, M>(packets))
+    }
 
-        Ok(response)
+    ///
+    /// Read signed packet written in envelopes
+    ///
+    /// The envelopes are checked, their header tells how to decode the data
+    /// (`Envelope::decode()` picks the serializer from the content type).
+    ///
+    pub fn read_signed_envelopes(
+        &mut self,
+        signed_packet_tag: String,
+    ) -> Result<Vec<Result<ReceivedMessage<Envelope, Envelope>>>> {
+        let packets = self.unwrap_packets(&signed_packet_tag, Some(MessageType::Signed))?;
+        Ok(Self::decode_envelopes(packets))
     }
 
     ///
     /// Read tagged packet
     ///
+    /// Each message is decoded on its own, failures are reported per message.
+    ///
     pub fn read_tagged(
         &mut self,
         tagged_packet_tag: String,
-    ) -> Result<Vec<(Option<String>, Option<String>)>> {
-        let mut response: Vec<(Option<String>, Option<String>)> = Vec::new();
+    ) -> Result<Vec<Result<ReceivedMessage>>> {
+        let packets = self.unwrap_packets(&tagged_packet_tag, Some(MessageType::Tagged))?;
+        Ok(Self::decode_strings(packets))
+    }
 
-        if self.is_connected {
-            let link = Address::from_str(&self.channel_address, &tagged_packet_tag).unwrap();
+    ///
+    /// Read tagged packet and deserialize the public and masked data
+    ///
+    /// Each message is decoded on its own, failures are reported per message.
+    ///
+    pub fn read_tagged_as<S, P, M>(
+        &mut self,
+        tagged_packet_tag: String,
+    ) -> Result<Vec<Result<ReceivedMessage<P, M>>>>
+    where
+        S: PayloadDeserializer<P> + PayloadDeserializer<M> + PayloadFormat,
+    {
+        let packets = self.unwrap_packets(&tagged_packet_tag, Some(MessageType::Tagged))?;
+        Ok(Self::decode_as::<S, P, M>(packets))
+    }
 
-            let message_list = iota_client::Client::get()
-                .recv_messages_with_options(&link, RecvOptions::default())?;
+    ///
+    /// Read tagged packet written in envelopes
+    ///
+    /// The envelopes are checked, their header tells how to decode the data
+    /// (`Envelope::decode()` picks the serializer from the content type).
+    ///
+    pub fn read_tagged_envelopes(
+        &mut self,
+        tagged_packet_tag: String,
+    ) -> Result<Vec<Result<ReceivedMessage<Envelope, Envelope>>>> {
+        let packets = self.unwrap_packets(&tagged_packet_tag, Some(MessageType::Tagged))?;
+        Ok(Self::decode_envelopes(packets))
+    }
 
-            for tx in message_list.iter() {
-                let header = tx.parse_header()?;
-                if header.check_content_type(message::TAGGED_PACKET) {
-                    match self.subscriber.unwrap_tagged_packet(header.clone()) {
-                        Ok((unwrapped_public, unwrapped_masked)) => {
-                            response.push((
-                                Payload::unwrap_data(
-                                    &String::from_utf8(unwrapped_public.0).unwrap(),
-                                )
-                                .unwrap(),
-                                Payload::unwrap_data(
-                                    &String::from_utf8(unwrapped_masked.0).unwrap(),
-                                )
-                                .unwrap(),
-                            ));
+    ///
+    /// Read a blob written with `write_signed_chunked` or `write_tagged_chunked`
+    ///
+    /// The chunks are read from the tags, in any order, then the blob is
+    /// reassembled and its SHA-256 checked. A chunk that can not be decoded
+    /// fails the read with its error.
+    ///
+    pub fn read_chunked(&mut self, chunk_tags: &[String]) -> Result<Vec<u8>> {
+        let mut assembler = ChunkAssembler::new();
+        for chunk_tag in chunk_tags.iter() {
+            let packets = self.unwrap_packets(chunk_tag, None)?;
+            for msg in Self::decode_strings(packets) {
+                if let Some(blob) = assembler.push(&msg?)? {
+                    return Ok(blob);
+                }
+            }
+        }
+        Err(ChannelError::MessageNotFound(format!(
+            "Only {} chunks found",
+            assembler.pending_chunks()
+        )))
+    }
+
+    ///
+    /// Fetch next messages
+    ///
+    /// Look for the messages following the ones already read, using the
+    /// channel sequencing state, so no tag has to be shared out-of-band.
+    /// Keyloads found on the way are applied. Each message is decoded on its
+    /// own, failures are reported per message.
+    ///
+    /// A message that can not be unwrapped, like a keyload shared with other
+    /// subscribers only, is stepped over and reported as
+    /// `ChannelError::SkippedMessage`, so that the next ones can still be read.
+    ///
+    /// The last message stepped over is saved by `export_state`, so that a
+    /// restored subscriber does not return the same messages again.
+    ///
+    pub fn fetch_next_messages(&mut self) -> Result<Vec<Result<ReceivedMessage>>> {
+        if !self.is_connected {
+            return Err(ChannelError::NotConnected);
+        }
+
+        let mut packets = Vec::new();
+        let mut visited = HashSet::new();
+        loop {
+            let mut found_new_msg = false;
+            for (publisher, cursor) in self.subscriber.gen_next_msg_ids(false) {
+                if !visited.insert(cursor.link.msgid.to_string()) {
+                    continue;
+                }
+                let message_list = self.transport.recv_messages_with_timestamp(&cursor.link)?;
+                if message_list.is_empty() {
+                    continue;
+                }
+                found_new_msg = true;
+
+                let msgid = cursor.link.msgid.to_string();
+                let mut failure = None;
+                for (tx, timestamp) in message_list.iter() {
+                    match self.unwrap_next(tx, *timestamp) {
+                        Ok(packet) => {
+                            self.step(msgid.clone(), packet.map(Ok), &mut packets);
+                            failure = None;
+                            break;
                         }
-                        Err(e) => println!("Tagged Packet Error: {}", e),
+                        Err(e) => failure = Some(e),
+                    }
+                }
+
+                if let Some(e) = failure {
+                    // Step over the message, unless the failed unwrap already did
+                    let stuck = self
+                        .subscriber
+                        .gen_next_msg_ids(false)
+                        .iter()
+                        .any(|(_, next)| next.link == cursor.link);
+                    if stuck {
+                        self.subscriber.store_state(publisher, cursor.link.clone());
                     }
+                    let skipped = Err(ChannelError::SkippedMessage {
+                        msgid: msgid.clone(),
+                        reason: e.to_string(),
+                    });
+                    self.step(msgid, Some(skipped), &mut packets);
                 }
             }
-        } else {
-            println!("Channel not connected");
+            if !found_new_msg {
+                break;
+            }
+        }
+        Ok(Self::decode_strings(packets))
+    }
+
+    ///
+    /// Stream of incoming messages
+    ///
+    /// Same as `AsyncChannel::subscribe_stream`, the channel is moved into
+    /// an `AsyncChannel` so that the polling runs on the blocking thread
+    /// pool. Convert the channel with `AsyncChannel::from` instead to keep
+    /// using it while the stream runs.
+    ///
+    pub fn subscribe_stream(self, interval: Duration) -> impl Stream<Item = Result<ReceivedMessage>>
+    where
+        T: Send + 'static,
+    {
+        super::AsyncChannel::from(self).subscribe_stream(interval)
+    }
+
+    ///
+    /// Unwrap every packet of the given type, or of any type, found at the tag
+    ///
+    fn unwrap_packets(
+        &mut self,
+        packet_tag: &str,
+        message_type: Option<MessageType>,
+    ) -> Result<Vec<Result<ReceivedMessage<Bytes, Bytes>>>> {
+        if !self.is_connected {
+            return Err(ChannelError::NotConnected);
+        }
+        let link = self.link(packet_tag)?;
+
+        let message_list = self.transport.recv_messages_with_timestamp(&link)?;
+        Ok(message_list
+            .iter()
+            .filter_map(|(tx, timestamp)| self.unwrap_packet(tx, *timestamp, message_type))
+            .collect())
+    }
+
+    ///
+    /// Move the saved cursor past a message of the sequence, keeping its
+    /// packet unless it was already returned before a restore
+    ///
+    fn step(
+        &mut self,
+        msgid: String,
+        packet: Option<Result<ReceivedMessage<Bytes, Bytes>>>,
+        packets: &mut Vec<Result<ReceivedMessage<Bytes, Bytes>>>,
+    ) {
+        match self.replay_to.take() {
+            Some(last_read) if last_read != msgid => self.replay_to = Some(last_read),
+            Some(_) => {}
+            None => packets.extend(packet),
+        }
+        self.cursor = Some(msgid);
+    }
+
+    ///
+    /// Unwrap the next message of the sequence, applying it if it is a keyload
+    ///
+    fn unwrap_next(
+        &mut self,
+        tx: &Message,
+        timestamp: Option<i64>,
+    ) -> Result<Option<ReceivedMessage<Bytes, Bytes>>> {
+        let header = tx.parse_header()?;
+        if header.check_content_type(message::KEYLOAD) {
+            self.subscriber.unwrap_keyload(header)?;
+            self.keyload = Some(StoredMessage::from(tx));
+            return Ok(None);
+        }
+        match self.unwrap_packet(tx, timestamp, None) {
+            Some(packet) => packet.map(Some),
+            None => Err(ChannelError::Streams(format!(
+                "Unexpected message type at {}",
+                tx.link.msgid
+            ))),
         }
+    }
+
+    ///
+    /// Unwrap a signed or tagged packet, returns None for other messages
+    ///
+    fn unwrap_packet(
+        &mut self,
+        tx: &Message,
+        timestamp: Option<i64>,
+        message_type: Option<MessageType>,
+    ) -> Option<Result<ReceivedMessage<Bytes, Bytes>>> {
+        let header = match tx.parse_header() {
+            Ok(header) => header,
+            Err(e) => return Some(Err(e.into())),
+        };
+        let linked_to = header.header.link.msgid.to_string();
+
+        let (message_type, packet) = if header.check_content_type(message::SIGNED_PACKET)
+            && message_type != Some(MessageType::Tagged)
+        {
+            let packet = self.subscriber.unwrap_signed_packet(header.clone()).map(
+                |(signer, unwrapped_public, unwrapped_masked)| {
+                    (
+                        Some(signer.tbits().to_string()),
+                        unwrapped_public,
+                        unwrapped_masked,
+                    )
+                },
+            );
+            (MessageType::Signed, packet)
+        } else if header.check_content_type(message::TAGGED_PACKET)
+            && message_type != Some(MessageType::Signed)
+        {
+            let packet = self.subscriber.unwrap_tagged_packet(header.clone()).map(
+                |(unwrapped_public, unwrapped_masked)| (None, unwrapped_public, unwrapped_masked),
+            );
+            (MessageType::Tagged, packet)
+        } else {
+            return None;
+        };
+
+        Some(
+            packet
+                .map(
+                    |(signer, unwrapped_public, unwrapped_masked)| ReceivedMessage {
+                        msgid: tx.link.msgid.to_string(),
+                        message_type: message_type,
+                        signer: signer,
+                        linked_to: linked_to,
+                        timestamp: timestamp,
+                        public: Some(unwrapped_public),
+                        masked: Some(unwrapped_masked),
+                    },
+                )
+                .map_err(ChannelError::from),
+        )
+    }
+
+    ///
+    /// Decode the packets as JSON strings
+    ///
+    fn decode_strings(
+        packets: Vec<Result<ReceivedMessage<Bytes, Bytes>>>,
+    ) -> Vec<Result<ReceivedMessage>> {
+        packets
+            .into_iter()
+            .map(|packet| {
+                packet?.try_map(
+                    |unwrapped_public| Payload::unwrap_data(&unwrapped_public.0),
+                    |unwrapped_masked| Payload::unwrap_data(&unwrapped_masked.0),
+                )
+            })
+            .collect()
+    }
+
+    fn decode_as<S, P, M>(
+        packets: Vec<Result<ReceivedMessage<Bytes, Bytes>>>,
+    ) -> Vec<Result<ReceivedMessage<P, M>>>
+    where
+        S: PayloadDeserializer<P> + PayloadDeserializer<M> + PayloadFormat,
+    {
+        packets
+            .into_iter()
+            .map(|packet| {
+                packet?.try_map(
+                    |unwrapped_public| payload::Payload::<S>::unwrap_data_as(&unwrapped_public.0),
+                    |unwrapped_masked| payload::Payload::<S>::unwrap_data_as(&unwrapped_masked.0),
+                )
+            })
+            .collect()
+    }
 
-        Ok(response)
+    fn decode_envelopes(
+        packets: Vec<Result<ReceivedMessage<Bytes, Bytes>>>,
+    ) -> Vec<Result<ReceivedMessage<Envelope, Envelope>>> {
+        packets
+            .into_iter()
+            .map(|packet| {
+                packet?.try_map(
+                    |unwrapped_public| Payload::unwrap_envelope(&unwrapped_public.0),
+                    |unwrapped_masked| Payload::unwrap_envelope(&unwrapped_masked.0),
+                )
+            })
+            .collect()
     }
 
     ///
     /// Update keyload
     ///
+    /// Wait for the keyload to appear, following the retry policy
+    ///
     pub fn update_keyload(&mut self, keyload_tag: String) -> Result<()> {
-        let keyload_link = Address::from_str(&self.channel_address, &keyload_tag).unwrap();
+        let keyload_link = self.link(&keyload_tag)?;
 
-        if self.is_connected {
-            let message_list = iota_client::Client::get()
-                .recv_messages_with_options(&keyload_link, RecvOptions::default())?;
+        if !self.is_connected {
+            return Err(ChannelError::NotConnected);
+        }
 
+        let retry = self.retry;
+        retry.run(|| {
+            let mut updated = Err(ChannelError::MessageNotFound(format!(
+                "No keyload message at {}",
+                keyload_tag
+            )));
+            let message_list = self.transport.recv_messages(&keyload_link)?;
             for tx in message_list.iter() {
                 let header = tx.parse_header()?;
                 if header.check_content_type(message::KEYLOAD) {
                     match self.subscriber.unwrap_keyload(header.clone()) {
                         Ok(_) => {
+                            self.keyload = Some(StoredMessage::from(tx));
+                            updated = Ok(());
                             break;
                         }
-                        Err(e) => println!("Keyload Packet Error: {}", e),
+                        Err(e) => updated = Err(e.into()),
                     }
-                } else {
-                    println!(
-                        "Expected a keyload message, found {}",
-                        header.content_type()
-                    );
                 }
             }
-        }
+            updated
+        })
+    }
+
+    ///
+    /// Send a message, following the retry policy
+    ///
+    fn send(&mut self, message: &Message) -> Result<()> {
+        let retry = self.retry;
+        retry.run(|| self.transport.send_message(message))
+    }
+
+    fn link(&self, tag: &str) -> Result<Address> {
+        Address::from_str(&self.channel_address, tag)
+            .map_err(|()| ChannelError::InvalidTag(tag.to_string()))
+    }
+}
+
+///
+/// Channel subscriber with async methods
+///
+pub type AsyncChannel<T = TangleTransport> = super::AsyncChannel<Channel<T>>;
 
-        Ok(())
+impl<T> super::AsyncChannel<Channel<T>>
+where
+    T: ChannelTransport + Send + 'static,
+{
+    ///
+    /// Connect
+    ///
+    pub async fn connect(&self) -> Result<String> {
+        run_blocking(&self.channel, |channel| channel.connect()).await
+    }
+
+    ///
+    /// Disconnect
+    ///
+    pub async fn disconnect(&self) -> Result<String> {
+        run_blocking(&self.channel, |channel| channel.disconnect()).await
+    }
+
+    ///
+    /// Read signed packet
+    ///
+    pub async fn read_signed(
+        &self,
+        signed_packet_tag: String,
+    ) -> Result<Vec<Result<ReceivedMessage>>> {
+        run_blocking(&self.channel, move |channel| {
+            channel.read_signed(signed_packet_tag)
+        })
+        .await
+    }
+
+    ///
+    /// Read signed packet and deserialize the public and masked data
+    ///
+    pub async fn read_signed_as<S, P, M>(
+        &self,
+        signed_packet_tag: String,
+    ) -> Result<Vec<Result<ReceivedMessage<P, M>>>>
+    where
+        S: PayloadDeserializer<P> + PayloadDeserializer<M> + PayloadFormat + 'static,
+        P: Send + 'static,
+        M: Send + 'static,
+    {
+        run_blocking(&self.channel, move |channel| {
+            channel.read_signed_as::<S, P, M>(signed_packet_tag)
+        })
+        .await
+    }
+
+    ///
+    /// Read signed packet written in envelopes
+    ///
+    pub async fn read_signed_envelopes(
+        &self,
+        signed_packet_tag: String,
+    ) -> Result<Vec<Result<ReceivedMessage<Envelope, Envelope>>>> {
+        run_blocking(&self.channel, move |channel| {
+            channel.read_signed_envelopes(signed_packet_tag)
+        })
+        .await
+    }
+
+    ///
+    /// Read tagged packet
+    ///
+    pub async fn read_tagged(
+        &self,
+        tagged_packet_tag: String,
+    ) -> Result<Vec<Result<ReceivedMessage>>> {
+        run_blocking(&self.channel, move |channel| {
+            channel.read_tagged(tagged_packet_tag)
+        })
+        .await
+    }
+
+    ///
+    /// Read tagged packet and deserialize the public and masked data
+    ///
+    pub async fn read_tagged_as<S, P, M>(
+        &self,
+        tagged_packet_tag: String,
+    ) -> Result<Vec<Result<ReceivedMessage<P, M>>>>
+    where
+        S: PayloadDeserializer<P> + PayloadDeserializer<M> + PayloadFormat + 'static,
+        P: Send + 'static,
+        M: Send + 'static,
+    {
+        run_blocking(&self.channel, move |channel| {
+            channel.read_tagged_as::<S, P, M>(tagged_packet_tag)
+        })
+        .await
+    }
+
+    ///
+    /// Read tagged packet written in envelopes
+    ///
+    pub async fn read_tagged_envelopes(
+        &self,
+        tagged_packet_tag: String,
+    ) -> Result<Vec<Result<ReceivedMessage<Envelope, Envelope>>>> {
+        run_blocking(&self.channel, move |channel| {
+            channel.read_tagged_envelopes(tagged_packet_tag)
+        })
+        .await
+    }
+
+    ///
+    /// Read a blob written as several packets
+    ///
+    pub async fn read_chunked(&self, chunk_tags: Vec<String>) -> Result<Vec<u8>> {
+        run_blocking(&self.channel, move |channel| {
+            channel.read_chunked(&chunk_tags)
+        })
+        .await
+    }
+
+    ///
+    /// Fetch next messages
+    ///
+    pub async fn fetch_next_messages(&self) -> Result<Vec<Result<ReceivedMessage>>> {
+        run_blocking(&self.channel, |channel| channel.fetch_next_messages()).await
+    }
+
+    ///
+    /// Update keyload
+    ///
+    pub async fn update_keyload(&self, keyload_tag: String) -> Result<()> {
+        run_blocking(&self.channel, move |channel| {
+            channel.update_keyload(keyload_tag)
+        })
+        .await
+    }
+
+    ///
+    /// Export the channel state, encrypted with the password
+    ///
+    pub async fn export_state(&self, password: String) -> Result<Vec<u8>> {
+        run_blocking(&self.channel, move |channel| {
+            channel.export_state(&password)
+        })
+        .await
+    }
+
+    ///
+    /// Stream of incoming messages
+    ///
+    /// Poll the transport for new messages every `interval`, keyloads are
+    /// applied on the way and the signed and tagged packets are yielded as
+    /// they arrive. Errors are yielded and the polling goes on after the next
+    /// interval, the stream ends once the subscriber is disconnected. The
+    /// polling runs on the blocking thread pool, it never stalls the runtime.
+    ///
+    pub fn subscribe_stream(
+        &self,
+        interval: Duration,
+    ) -> impl Stream<Item = Result<ReceivedMessage>> {
+        stream::unfold(
+            (self.clone(), VecDeque::new(), false),
+            move |(channel, mut pending, mut wait)| async move {
+                loop {
+                    if let Some(msg) = pending.pop_front() {
+                        return Some((msg, (channel, pending, false)));
+                    }
+                    if wait {
+                        tokio::time::delay_for(interval).await;
+                    }
+                    match channel.fetch_next_messages().await {
+                        Ok(messages) => {
+                            wait = messages.is_empty();
+                            pending.extend(messages);
+                        }
+                        Err(ChannelError::NotConnected) => return None,
+                        Err(e) => return Some((Err(e), (channel, pending, true))),
+                    }
+                }
+            },
+        )
     }
 }
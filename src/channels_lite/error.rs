@@ -0,0 +1,127 @@
+//!
+//! Channel errors
+//!
+use std::fmt;
+
+///
+/// Result returned by the channels
+///
+pub type Result<T> = std::result::Result<T, ChannelError>;
+
+///
+/// Channel Error
+///
+#[derive(Debug, Clone, PartialEq)]
+pub enum ChannelError {
+    /// The subscriber is not connected to the channel
+    ///
+    NotConnected,
+    /// A masked message was requested before any keyload was shared
+    ///
+    NoKeyload,
+    /// The tag can not be turned into a link of the channel
+    ///
+    InvalidTag(String),
+    /// No valid announcement was found at the announcement link
+    ///
+    AnnouncementNotFound,
+    /// No valid message of the expected type was found at the link
+    ///
+    MessageNotFound(String),
+    /// A message of the channel sequence can not be unwrapped and was skipped
+    ///
+    SkippedMessage {
+        /// Tag of the skipped message
+        ///
+        msgid: String,
+        /// Why the message can not be unwrapped
+        ///
+        reason: String,
+    },
+    /// The subscriber is not known by the author
+    ///
+    UnknownSubscriber(String),
+    /// A keyload was requested for no subscriber
+    ///
+    NoSubscriber,
+    /// The payload can not be built or decoded
+    ///
+    MalformedPayload(String),
+    /// The encoded payload does not fit in a message
+    ///
+    PayloadTooLarge {
+        /// Encoded size in bytes
+        ///
+        size: usize,
+        /// Maximum encoded size in bytes
+        ///
+        max: usize,
+    },
+    /// The exported state can not be restored
+    ///
+    InvalidState(String),
+    /// The transport failed to send or receive messages
+    ///
+    Transport(String),
+    /// IOTA Streams failed to wrap or unwrap a message
+    ///
+    Streams(String),
+    /// The background task running a channel operation failed
+    ///
+    Task(String),
+    /// The outbound queue can not be used or persisted
+    ///
+    Queue(String),
+    /// A chunked blob was only partly written
+    ///
+    PartialWrite {
+        /// Tags of the chunks written, in order
+        ///
+        tags: Vec<String>,
+        /// Why the next chunk was not written
+        ///
+        reason: Box<ChannelError>,
+    },
+}
+
+impl fmt::Display for ChannelError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::NotConnected => write!(f, "Channel not connected"),
+            Self::NoKeyload => write!(
+                f,
+                "Can not send masked message before a keyload is generated and shared"
+            ),
+            Self::InvalidTag(tag) => write!(f, "Invalid tag {}", tag),
+            Self::AnnouncementNotFound => write!(f, "No valid announce message found"),
+            Self::MessageNotFound(reason) => write!(f, "Message not found: {}", reason),
+            Self::SkippedMessage { msgid, reason } => {
+                write!(f, "Skipped message {}: {}", msgid, reason)
+            }
+            Self::UnknownSubscriber(id) => write!(f, "Unknown subscriber {}", id),
+            Self::NoSubscriber => write!(f, "No subscriber to share the keyload with"),
+            Self::MalformedPayload(reason) => write!(f, "Malformed payload: {}", reason),
+            Self::PayloadTooLarge { size, max } => write!(
+                f,
+                "Payload too large: {} bytes encoded, at most {} allowed",
+                size, max
+            ),
+            Self::InvalidState(reason) => write!(f, "Invalid channel state: {}", reason),
+            Self::Transport(reason) => write!(f, "Transport error: {}", reason),
+            Self::Streams(reason) => write!(f, "Streams error: {}", reason),
+            Self::Task(reason) => write!(f, "Task error: {}", reason),
+            Self::Queue(reason) => write!(f, "Outbound queue error: {}", reason),
+            Self::PartialWrite { tags, reason } => {
+                write!(f, "Only {} chunks written: {}", tags.len(), reason)
+            }
+        }
+    }
+}
+
+impl std::error::Error for ChannelError {}
+
+impl From<anyhow::Error> for ChannelError {
+    fn from(error: anyhow::Error) -> Self {
+        Self::Streams(error.to_string())
+    }
+}
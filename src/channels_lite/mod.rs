@@ -3,68 +3,113 @@
 //!
 pub mod channel_author;
 pub mod channel_subscriber;
-use iota_streams::app::transport::tangle::client::SendTrytesOptions;
+mod error;
+mod network;
+mod queue;
+mod retry;
+mod state;
+pub mod transport;
+
+pub use error::{ChannelError, Result};
+use iota_streams::core_edsig::key_exchange::ntru;
+pub use network::{Network, NetworkConfig, PowMode, Timeouts};
+pub use queue::QueuedMessage;
+pub use retry::RetryPolicy;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::sync::{Arc, Mutex};
 
 ///
-/// Network Urls
+/// Subscriber identifier
 ///
-/// Pre-defined iota network urls
+/// Derived from the NTRU public key the subscriber sent in its subscription
 ///
-pub enum Network {
-    /// Main network
-    ///
-    Main,
-    /// Dev network
-    ///
-    Devnet,
-    /// Community network
-    ///
-    Comnet,
-    /// Custom network URL
+#[derive(Clone, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct SubscriberId(String);
+
+impl SubscriberId {
     ///
-    /// Arguments:
-    ///     * Custome url
-    ///     * Min weight magnitude
+    /// Identifier as string
     ///
-    Custom(&'static str, u8),
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
 }
 
-impl Network {
-    ///
-    /// To string
-    ///
-    pub fn as_string(&self) -> &'static str {
-        match self {
-            Self::Custom(url, _) => url,
-            Self::Main => "https://nodes.thetangle.org:443",
-            Self::Comnet => "https://nodes.comnet.thetangle.org:443",
-            Self::Devnet => "https://nodes.devnet.iota.org:443",
+impl From<&ntru::PublicKey> for SubscriberId {
+    fn from(public_key: &ntru::PublicKey) -> Self {
+        SubscriberId(public_key.get_pkid().to_string())
+    }
+}
+
+impl fmt::Display for SubscriberId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+///
+/// Channel with async methods
+///
+/// Wraps a `channel_author::Channel` or a `channel_subscriber::Channel`. Every
+/// operation runs on the tokio blocking thread pool, so the node requests
+/// never stall the runtime. Clones share the same channel.
+///
+pub struct AsyncChannel<C> {
+    channel: Arc<Mutex<C>>,
+}
+
+impl<C> Clone for AsyncChannel<C> {
+    fn clone(&self) -> Self {
+        Self {
+            channel: self.channel.clone(),
         }
     }
+}
 
+impl<C> From<C> for AsyncChannel<C> {
+    fn from(channel: C) -> Self {
+        Self {
+            channel: Arc::new(Mutex::new(channel)),
+        }
+    }
+}
+
+impl<C> AsyncChannel<C>
+where
+    C: Send + 'static,
+{
     ///
-    /// Send Options
+    /// Run a custom operation on the underlying channel
     ///
-    pub fn send_options(&self) -> SendTrytesOptions {
-        let mut send_opt = SendTrytesOptions::default();
-        match self {
-            Self::Custom(_, mwm) => {
-                send_opt.min_weight_magnitude = *mwm;
-                send_opt.local_pow = false;
-            }
-            Self::Main => {
-                send_opt.min_weight_magnitude = 14;
-                send_opt.local_pow = false;
-            }
-            Self::Comnet => {
-                send_opt.min_weight_magnitude = 10;
-                send_opt.local_pow = false;
-            }
-            Self::Devnet => {
-                send_opt.min_weight_magnitude = 9;
-                send_opt.local_pow = false;
-            }
-        }
-        send_opt
+    pub async fn with_channel<F, R>(&self, operation: F) -> Result<R>
+    where
+        F: FnOnce(&mut C) -> Result<R> + Send + 'static,
+        R: Send + 'static,
+    {
+        run_blocking(&self.channel, operation).await
     }
 }
+
+///
+/// Run a channel operation on the blocking thread pool
+///
+/// The node requests and the PoW wait block, so they must not run on the
+/// executor threads.
+///
+pub(crate) async fn run_blocking<C, F, R>(channel: &Arc<Mutex<C>>, operation: F) -> Result<R>
+where
+    C: Send + 'static,
+    F: FnOnce(&mut C) -> Result<R> + Send + 'static,
+    R: Send + 'static,
+{
+    let channel = channel.clone();
+    tokio::task::spawn_blocking(move || {
+        let mut channel = channel
+            .lock()
+            .map_err(|_| ChannelError::Task("Channel lock poisoned".to_string()))?;
+        operation(&mut channel)
+    })
+    .await
+    .map_err(|e| ChannelError::Task(e.to_string()))?
+}
@@ -0,0 +1,234 @@
+//!
+//! Network configuration
+//!
+use iota_streams::app::transport::tangle::client::SendTrytesOptions;
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+
+const DEFAULT_DEPTH: u8 = 3;
+const DEFAULT_HEALTH_CHECK_MS: u64 = 60_000;
+const DEFAULT_PROBE_MS: u64 = 10_000;
+const DEFAULT_SEND_MS: u64 = 120_000;
+const DEFAULT_RECV_MS: u64 = 30_000;
+
+///
+/// Network Urls
+///
+/// Pre-defined iota network urls, to be turned into a `NetworkConfig`
+///
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Network {
+    /// Main network
+    ///
+    Main,
+    /// Dev network
+    ///
+    Devnet,
+    /// Community network
+    ///
+    Comnet,
+    /// Custom network URL
+    ///
+    /// Arguments:
+    ///     * Custome url
+    ///     * Min weight magnitude
+    ///
+    Custom(&'static str, u8),
+}
+
+impl Network {
+    ///
+    /// To string
+    ///
+    pub fn as_string(&self) -> &'static str {
+        match self {
+            Self::Custom(url, _) => url,
+            Self::Main => "https://nodes.thetangle.org:443",
+            Self::Comnet => "https://nodes.comnet.thetangle.org:443",
+            Self::Devnet => "https://nodes.devnet.iota.org:443",
+        }
+    }
+
+    ///
+    /// Min weight magnitude of the network
+    ///
+    pub fn min_weight_magnitude(&self) -> u8 {
+        match self {
+            Self::Custom(_, mwm) => *mwm,
+            Self::Main => 14,
+            Self::Comnet => 10,
+            Self::Devnet => 9,
+        }
+    }
+
+    ///
+    /// Send Options
+    ///
+    pub fn send_options(&self) -> SendTrytesOptions {
+        NetworkConfig::from(*self).send_options()
+    }
+}
+
+///
+/// Where the proof of work is done
+///
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PowMode {
+    /// The node does the PoW (`attachToTangle`)
+    ///
+    Remote,
+    /// The PoW is done on this machine, for the configured MWM
+    ///
+    Local,
+}
+
+impl Default for PowMode {
+    fn default() -> Self {
+        Self::Remote
+    }
+}
+
+///
+/// Timeouts of the node requests, in milliseconds
+///
+/// Sends time out after 2 minutes and receives after 30 seconds by default,
+/// `None` waits as long as the node takes.
+///
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Timeouts {
+    /// Timeout of a send, PoW included. The node may still attach the
+    /// message afterwards, so it is looked up before sending it again.
+    ///
+    pub send_ms: Option<u64>,
+    /// Timeout of a receive
+    ///
+    pub recv_ms: Option<u64>,
+    /// Timeout of a node health probe
+    ///
+    pub probe_ms: Option<u64>,
+}
+
+impl Default for Timeouts {
+    fn default() -> Self {
+        Self {
+            send_ms: Some(DEFAULT_SEND_MS),
+            recv_ms: Some(DEFAULT_RECV_MS),
+            probe_ms: None,
+        }
+    }
+}
+
+impl Timeouts {
+    ///
+    /// Send timeout
+    ///
+    pub fn send(&self) -> Option<Duration> {
+        self.send_ms.map(Duration::from_millis)
+    }
+
+    ///
+    /// Receive timeout
+    ///
+    pub fn recv(&self) -> Option<Duration> {
+        self.recv_ms.map(Duration::from_millis)
+    }
+
+    ///
+    /// Health probe timeout, 10 seconds by default
+    ///
+    pub fn probe(&self) -> Duration {
+        Duration::from_millis(self.probe_ms.unwrap_or(DEFAULT_PROBE_MS))
+    }
+}
+
+///
+/// Network configuration
+///
+/// Owned counterpart of `Network`, so it can be loaded from a config file or
+/// the environment. Missing fields take the mainnet values.
+///
+/// ```json
+/// { "nodes": ["http://localhost:14265"], "min_weight_magnitude": 9, "pow": "local" }
+/// ```
+///
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct NetworkConfig {
+    /// Node urls
+    ///
+    pub nodes: Vec<String>,
+    /// Min weight magnitude
+    ///
+    pub min_weight_magnitude: u8,
+    /// Depth used to select the tips
+    ///
+    pub depth: u8,
+    /// Local or remote PoW
+    ///
+    pub pow: PowMode,
+    /// Worker threads of the local PoW, every core by default
+    ///
+    pub pow_threads: Option<usize>,
+    /// Request timeouts
+    ///
+    pub timeouts: Timeouts,
+    /// Interval between two health checks of the nodes, in milliseconds
+    ///
+    pub health_check_ms: u64,
+}
+
+impl NetworkConfig {
+    ///
+    /// Configuration of a single node
+    ///
+    pub fn custom<U: Into<String>>(url: U, min_weight_magnitude: u8) -> Self {
+        Self {
+            nodes: vec![url.into()],
+            min_weight_magnitude: min_weight_magnitude,
+            ..Self::default()
+        }
+    }
+
+    ///
+    /// Interval between two health checks of the nodes
+    ///
+    pub fn health_check_interval(&self) -> Duration {
+        Duration::from_millis(self.health_check_ms)
+    }
+
+    ///
+    /// Send Options
+    ///
+    pub fn send_options(&self) -> SendTrytesOptions {
+        let mut send_opt = SendTrytesOptions::default();
+        send_opt.min_weight_magnitude = self.min_weight_magnitude;
+        send_opt.depth = self.depth;
+        send_opt.local_pow = self.pow == PowMode::Local;
+        if let Some(threads) = self.pow_threads {
+            send_opt.threads = threads;
+        }
+        send_opt
+    }
+}
+
+impl Default for NetworkConfig {
+    fn default() -> Self {
+        Self::from(Network::Main)
+    }
+}
+
+impl From<Network> for NetworkConfig {
+    fn from(network: Network) -> Self {
+        Self {
+            nodes: vec![network.as_string().to_string()],
+            min_weight_magnitude: network.min_weight_magnitude(),
+            depth: DEFAULT_DEPTH,
+            pow: PowMode::Remote,
+            pow_threads: None,
+            timeouts: Timeouts::default(),
+            health_check_ms: DEFAULT_HEALTH_CHECK_MS,
+        }
+    }
+}
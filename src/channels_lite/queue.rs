@@ -0,0 +1,324 @@
+//!
+//! Outbound message queue
+//!
+//! Messages are wrapped by the author when enqueued, so their place in the
+//! link chain is fixed, then kept in a file until a background thread sends
+//! them, in order, through its own transport.
+//!
+//! The file also holds a checkpoint of the author, written in the same
+//! atomic update as the messages, so that a restarted author never wraps a
+//! message again in a slot already taken by a queued or sent one.
+//!
+use super::state::StoredMessage;
+use super::transport::{ChannelTransport, SendReport, MAX_SEND_REPORTS};
+use super::{ChannelError, Result, RetryPolicy};
+use futures::channel::oneshot;
+use iota_streams::app_channels::api::tangle::Message;
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+use std::fs::{self, File};
+use std::future::Future;
+use std::io::BufReader;
+use std::path::{Path, PathBuf};
+use std::pin::Pin;
+use std::sync::{Arc, Condvar, Mutex, MutexGuard};
+use std::task::{Context, Poll};
+use std::thread::{self, JoinHandle};
+use std::time::Instant;
+
+///
+/// Message waiting in the outbound queue
+///
+/// Resolves to the message tag once the message is sent, or to a `Queue`
+/// error when it was sent but could not be removed from the file.
+///
+pub struct QueuedMessage {
+    msgid: String,
+    sent: oneshot::Receiver<Result<String>>,
+}
+
+impl QueuedMessage {
+    ///
+    /// Tag of the message, known before it is sent
+    ///
+    pub fn tag(&self) -> &str {
+        &self.msgid
+    }
+
+    ///
+    /// Block until the message is sent
+    ///
+    pub fn wait(self) -> Result<String> {
+        futures::executor::block_on(self)
+    }
+}
+
+impl Future for QueuedMessage {
+    type Output = Result<String>;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        Pin::new(&mut self.sent).poll(cx).map(|sent| {
+            sent.unwrap_or_else(|_| {
+                Err(ChannelError::Queue(
+                    "Outbound queue stopped before sending the message".to_string(),
+                ))
+            })
+        })
+    }
+}
+
+struct Pending {
+    message: StoredMessage,
+    sent: Option<oneshot::Sender<Result<String>>>,
+}
+
+///
+/// Content of the queue file
+///
+#[derive(Serialize, Deserialize, Default)]
+struct QueueFile {
+    checkpoint: Option<serde_json::Value>,
+    messages: Vec<StoredMessage>,
+}
+
+impl QueueFile {
+    fn load(path: &Path) -> Result<Self> {
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        let file = File::open(path)
+            .map_err(|e| ChannelError::Queue(format!("{}: {}", path.display(), e)))?;
+        serde_json::from_reader(BufReader::new(file))
+            .map_err(|e| ChannelError::Queue(format!("{}: {}", path.display(), e)))
+    }
+}
+
+struct QueueState {
+    path: PathBuf,
+    checkpoint: serde_json::Value,
+    pending: VecDeque<Pending>,
+    reports: VecDeque<SendReport>,
+    shutdown: bool,
+}
+
+impl QueueState {
+    ///
+    /// Write the checkpoint and the pending messages to the file, through a
+    /// temporary file so that a crash never leaves a truncated queue
+    ///
+    fn persist(&self) -> Result<()> {
+        let bytes = serde_json::to_vec(&QueueFile {
+            checkpoint: Some(self.checkpoint.clone()),
+            messages: self.pending.iter().map(|p| p.message.clone()).collect(),
+        })
+        .map_err(|e| ChannelError::Queue(e.to_string()))?;
+
+        let tmp = self.path.with_extension("tmp");
+        fs::write(&tmp, bytes)
+            .and_then(|()| fs::rename(&tmp, &self.path))
+            .map_err(|e| ChannelError::Queue(format!("{}: {}", self.path.display(), e)))
+    }
+}
+
+struct Shared {
+    state: Mutex<QueueState>,
+    wakeup: Condvar,
+}
+
+impl Shared {
+    fn lock(&self) -> MutexGuard<'_, QueueState> {
+        self.state.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+///
+/// Durable queue flushed by a background thread
+///
+/// Dropping the queue stops the thread and waits for the send in progress,
+/// if any, so that the file is no longer written once the queue is gone.
+///
+pub(crate) struct OutboundQueue {
+    shared: Arc<Shared>,
+    flusher: Option<JoinHandle<()>>,
+}
+
+impl OutboundQueue {
+    ///
+    /// Checkpoint written with the last message enqueued in the file, if any
+    ///
+    pub(crate) fn checkpoint<P: AsRef<Path>>(path: P) -> Result<Option<serde_json::Value>> {
+        Ok(QueueFile::load(path.as_ref())?.checkpoint)
+    }
+
+    ///
+    /// Open the queue file, resuming the messages left by a previous run,
+    /// and start flushing
+    ///
+    /// The checkpoint replaces the one of the file, it must not be older.
+    ///
+    pub(crate) fn open<P, T>(
+        path: P,
+        transport: T,
+        retry: RetryPolicy,
+        checkpoint: serde_json::Value,
+    ) -> Result<Self>
+    where
+        P: AsRef<Path>,
+        T: ChannelTransport + Send + 'static,
+    {
+        let path = path.as_ref().to_path_buf();
+        let pending = QueueFile::load(&path)?
+            .messages
+            .into_iter()
+            .map(|message| Pending {
+                message: message,
+                sent: None,
+            })
+            .collect();
+
+        let shared = Arc::new(Shared {
+            state: Mutex::new(QueueState {
+                path: path,
+                checkpoint: checkpoint,
+                pending: pending,
+                reports: VecDeque::new(),
+                shutdown: false,
+            }),
+            wakeup: Condvar::new(),
+        });
+        shared.lock().persist()?;
+
+        let flusher = {
+            let shared = shared.clone();
+            thread::spawn(move || flush(shared, transport, retry))
+        };
+
+        Ok(Self {
+            shared: shared,
+            flusher: Some(flusher),
+        })
+    }
+
+    ///
+    /// Add a message at the end of the queue, along with the checkpoint of
+    /// the author that wrapped it
+    ///
+    pub(crate) fn push(
+        &self,
+        message: &Message,
+        checkpoint: serde_json::Value,
+    ) -> Result<QueuedMessage> {
+        let message = StoredMessage::from(message);
+        let msgid = message.msgid().to_string();
+        let (sender, receiver) = oneshot::channel();
+
+        let mut state = self.shared.lock();
+        let previous = std::mem::replace(&mut state.checkpoint, checkpoint);
+        state.pending.push_back(Pending {
+            message: message,
+            sent: Some(sender),
+        });
+        if let Err(e) = state.persist() {
+            state.pending.pop_back();
+            state.checkpoint = previous;
+            return Err(e);
+        }
+        self.shared.wakeup.notify_all();
+
+        Ok(QueuedMessage {
+            msgid: msgid,
+            sent: receiver,
+        })
+    }
+
+    ///
+    /// Number of messages not sent yet
+    ///
+    pub(crate) fn len(&self) -> usize {
+        self.shared.lock().pending.len()
+    }
+
+    ///
+    /// Reports of the messages sent by the background thread since the last
+    /// call, oldest first
+    ///
+    pub(crate) fn take_send_reports(&self) -> Vec<SendReport> {
+        self.shared.lock().reports.drain(..).collect()
+    }
+}
+
+impl Drop for OutboundQueue {
+    fn drop(&mut self) {
+        self.shared.lock().shutdown = true;
+        self.shared.wakeup.notify_all();
+        if let Some(flusher) = self.flusher.take() {
+            let _ = flusher.join();
+        }
+    }
+}
+
+///
+/// Send the messages in order, the first one is retried until the transport
+/// accepts it, so that no message ever overtakes another
+///
+fn flush<T: ChannelTransport>(shared: Arc<Shared>, mut transport: T, retry: RetryPolicy) {
+    let mut failures = 0;
+    loop {
+        let message = {
+            let mut state = shared.lock();
+            while state.pending.is_empty() && !state.shutdown {
+                state = shared.wakeup.wait(state).unwrap_or_else(|e| e.into_inner());
+            }
+            if state.shutdown {
+                return;
+            }
+            state.pending[0].message.clone()
+        };
+
+        let sent = message
+            .to_message()
+            .and_then(|msg| transport.send_message(&msg));
+
+        let mut state = shared.lock();
+        for report in transport.take_send_reports() {
+            if state.reports.len() == MAX_SEND_REPORTS {
+                state.reports.pop_front();
+            }
+            state.reports.push_back(report);
+        }
+        match sent {
+            Err(ChannelError::Transport(_)) => {
+                failures += 1;
+                let retry_at = Instant::now() + retry.backoff(failures);
+                while !state.shutdown && Instant::now() < retry_at {
+                    state = shared
+                        .wakeup
+                        .wait_timeout(state, retry_at.saturating_duration_since(Instant::now()))
+                        .map(|(state, _)| state)
+                        .unwrap_or_else(|e| e.into_inner().0);
+                }
+            }
+            result => {
+                failures = 0;
+                if let Some(done) = state.pending.pop_front() {
+                    // Still in the file, the message would be sent again
+                    // after a restart unless a later update succeeds
+                    let persisted = state.persist().map_err(|e| {
+                        ChannelError::Queue(format!(
+                            "{} sent but still queued in the file: {}",
+                            done.message.msgid(),
+                            e
+                        ))
+                    });
+                    if let Some(sent) = done.sent {
+                        let _ = sent.send(
+                            result
+                                .and(persisted)
+                                .map(|()| done.message.msgid().to_string()),
+                        );
+                    }
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,242 @@
+//!
+//! Retry policy
+//!
+use super::{ChannelError, Result};
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+use std::thread;
+use std::time::{Duration, Instant};
+
+///
+/// Retry policy of the channel requests
+///
+/// Failed sends and receives, and messages not found yet (not propagated),
+/// are tried again after an exponential backoff, until `max_attempts` or
+/// the deadline is reached. Other errors are returned right away.
+///
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RetryPolicy {
+    /// Attempts before giving up, the first one included
+    ///
+    pub max_attempts: u32,
+    /// Delay before the first retry, in milliseconds
+    ///
+    pub initial_backoff_ms: u64,
+    /// Upper bound of the delay between two attempts, in milliseconds
+    ///
+    pub max_backoff_ms: u64,
+    /// Factor applied to the delay after each attempt
+    ///
+    pub multiplier: f64,
+    /// Random part of the delay, from 0 (none) to 1 (up to twice the delay),
+    /// NaN counts as none
+    ///
+    pub jitter: f64,
+    /// Time after which no more attempt is made, in milliseconds
+    ///
+    pub deadline_ms: Option<u64>,
+}
+
+impl RetryPolicy {
+    ///
+    /// Single attempt, errors are returned right away
+    ///
+    pub fn none() -> Self {
+        Self {
+            max_attempts: 1,
+            ..Self::default()
+        }
+    }
+
+    ///
+    /// Delay before the given retry (1 for the first one), jitter excluded
+    ///
+    pub fn backoff(&self, retry: u32) -> Duration {
+        let factor = self
+            .multiplier
+            .max(1.0)
+            .powi(retry.saturating_sub(1) as i32);
+        let delay = (self.initial_backoff_ms as f64 * factor).min(self.max_backoff_ms as f64);
+        Duration::from_millis(delay as u64)
+    }
+
+    ///
+    /// Run the operation until it succeeds or the policy gives up,
+    /// the last error is returned then
+    ///
+    pub(crate) fn run<R, F>(&self, mut operation: F) -> Result<R>
+    where
+        F: FnMut() -> Result<R>,
+    {
+        let start = Instant::now();
+        let mut attempt = 1;
+        loop {
+            let error = match operation() {
+                Ok(result) => return Ok(result),
+                Err(e) => e,
+            };
+            if !is_retryable(&error) || attempt >= self.max_attempts {
+                return Err(error);
+            }
+
+            let delay = self.with_jitter(self.backoff(attempt));
+            if let Some(deadline) = self.deadline_ms.map(Duration::from_millis) {
+                if start.elapsed() + delay > deadline {
+                    return Err(error);
+                }
+            }
+            thread::sleep(delay);
+            attempt += 1;
+        }
+    }
+
+    fn with_jitter(&self, delay: Duration) -> Duration {
+        if self.jitter.is_nan() {
+            return delay;
+        }
+        let jitter = self.jitter.clamp(0.0, 1.0);
+        if jitter == 0.0 {
+            return delay;
+        }
+        let factor = 1.0 + rand::thread_rng().gen_range(-jitter, jitter);
+        Duration::from_millis((delay.as_millis() as f64 * factor) as u64)
+    }
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_attempts: 5,
+            initial_backoff_ms: 1_000,
+            max_backoff_ms: 16_000,
+            multiplier: 2.0,
+            jitter: 0.2,
+            deadline_ms: Some(60_000),
+        }
+    }
+}
+
+///
+/// Transport failures and messages not propagated yet are worth a retry
+///
+fn is_retryable(error: &ChannelError) -> bool {
+    matches!(
+        error,
+        ChannelError::Transport(_)
+            | ChannelError::MessageNotFound(_)
+            | ChannelError::AnnouncementNotFound
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::Cell;
+
+    fn policy(max_attempts: u32, deadline_ms: Option<u64>) -> RetryPolicy {
+        RetryPolicy {
+            max_attempts: max_attempts,
+            initial_backoff_ms: 50,
+            max_backoff_ms: 1_000,
+            multiplier: 2.0,
+            jitter: 0.0,
+            deadline_ms: deadline_ms,
+        }
+    }
+
+    fn failing(attempts: &Cell<u32>, error: ChannelError) -> impl FnMut() -> Result<()> + '_ {
+        let mut error = Some(error);
+        move || {
+            attempts.set(attempts.get() + 1);
+            match attempts.get() {
+                1 => Err(error.take().unwrap()),
+                _ => Err(ChannelError::Transport("No connectivity".to_string())),
+            }
+        }
+    }
+
+    #[test]
+    fn backoff_grows_up_to_the_max() {
+        let policy = RetryPolicy::default();
+        let delays: Vec<u64> = (1..=6)
+            .map(|retry| policy.backoff(retry).as_millis() as u64)
+            .collect();
+        assert_eq!(delays, vec![1_000, 2_000, 4_000, 8_000, 16_000, 16_000]);
+    }
+
+    #[test]
+    fn gives_up_after_max_attempts() {
+        let attempts = Cell::new(0);
+        let policy = RetryPolicy {
+            initial_backoff_ms: 0,
+            ..policy(3, None)
+        };
+        match policy.run(failing(&attempts, ChannelError::AnnouncementNotFound)) {
+            Err(ChannelError::Transport(_)) => {}
+            other => panic!("Expected Transport, got {:?}", other),
+        }
+        assert_eq!(attempts.get(), 3);
+    }
+
+    #[test]
+    fn gives_up_before_the_deadline() {
+        // Retried after 50 ms, the next retry after 100 ms more is too late
+        let attempts = Cell::new(0);
+        let result = policy(10, Some(120)).run(failing(
+            &attempts,
+            ChannelError::MessageNotFound("tag".to_string()),
+        ));
+        assert!(result.is_err());
+        assert_eq!(attempts.get(), 2);
+    }
+
+    #[test]
+    fn succeeds_after_a_retry() {
+        let attempts = Cell::new(0);
+        let result = policy(3, None).run(|| {
+            attempts.set(attempts.get() + 1);
+            match attempts.get() {
+                1 => Err(ChannelError::Transport("No connectivity".to_string())),
+                n => Ok(n),
+            }
+        });
+        assert_eq!(result.unwrap(), 2);
+    }
+
+    #[test]
+    fn only_transport_and_lookup_errors_are_retried() {
+        assert!(is_retryable(&ChannelError::Transport("down".to_string())));
+        assert!(is_retryable(&ChannelError::MessageNotFound(
+            "tag".to_string()
+        )));
+        assert!(is_retryable(&ChannelError::AnnouncementNotFound));
+        assert!(!is_retryable(&ChannelError::NotConnected));
+        assert!(!is_retryable(&ChannelError::InvalidTag("tag".to_string())));
+
+        let attempts = Cell::new(0);
+        match policy(3, None).run(failing(&attempts, ChannelError::NoKeyload)) {
+            Err(ChannelError::NoKeyload) => {}
+            other => panic!("Expected NoKeyload, got {:?}", other),
+        }
+        assert_eq!(attempts.get(), 1);
+    }
+
+    #[test]
+    fn jitter_stays_in_bounds() {
+        let delay = Duration::from_millis(1_000);
+        let nan = RetryPolicy {
+            jitter: f64::NAN,
+            ..policy(1, None)
+        };
+        assert_eq!(nan.with_jitter(delay), delay);
+
+        let full = RetryPolicy {
+            jitter: 5.0,
+            ..policy(1, None)
+        };
+        for _ in 0..100 {
+            assert!(full.with_jitter(delay) <= Duration::from_millis(2_000));
+        }
+    }
+}
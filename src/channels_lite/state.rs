@@ -0,0 +1,115 @@
+//!
+//! Channel state snapshots
+//!
+use super::{ChannelError, Result};
+use chacha20poly1305::aead::{Aead, NewAead};
+use chacha20poly1305::{ChaCha20Poly1305, Key, Nonce};
+use hmac::Hmac;
+use iota_streams::app_channels::api::tangle::{Address, Message};
+use iota_streams::core::tbits::Tbits;
+use rand::{rngs::OsRng, RngCore};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use sha2::Sha256;
+
+const SNAPSHOT_VERSION: u8 = 1;
+const SALT_LEN: usize = 16;
+const NONCE_LEN: usize = 12;
+const KDF_ROUNDS: u32 = 100_000;
+
+///
+/// Serialize the state and encrypt it with a key derived from the password
+///
+/// Layout: `version || salt || nonce || ciphertext`
+///
+pub(crate) fn seal<S: Serialize>(state: &S, password: &str) -> Result<Vec<u8>> {
+    let plaintext =
+        serde_json::to_vec(state).map_err(|e| ChannelError::InvalidState(e.to_string()))?;
+
+    let mut salt = [0u8; SALT_LEN];
+    let mut nonce = [0u8; NONCE_LEN];
+    OsRng.fill_bytes(&mut salt);
+    OsRng.fill_bytes(&mut nonce);
+
+    let ciphertext = cipher(password, &salt)
+        .encrypt(Nonce::from_slice(&nonce), plaintext.as_ref())
+        .map_err(|_| ChannelError::InvalidState("Failed to encrypt the state".to_string()))?;
+
+    let mut bytes = Vec::with_capacity(1 + SALT_LEN + NONCE_LEN + ciphertext.len());
+    bytes.push(SNAPSHOT_VERSION);
+    bytes.extend_from_slice(&salt);
+    bytes.extend_from_slice(&nonce);
+    bytes.extend_from_slice(&ciphertext);
+    Ok(bytes)
+}
+
+///
+/// Decrypt and deserialize a state produced by `seal`
+///
+pub(crate) fn unseal<S: DeserializeOwned>(bytes: &[u8], password: &str) -> Result<S> {
+    if bytes.len() < 1 + SALT_LEN + NONCE_LEN {
+        return Err(ChannelError::InvalidState("Truncated state".to_string()));
+    }
+    if bytes[0] != SNAPSHOT_VERSION {
+        return Err(ChannelError::InvalidState(format!(
+            "Unsupported version {}",
+            bytes[0]
+        )));
+    }
+    let (salt, rest) = bytes[1..].split_at(SALT_LEN);
+    let (nonce, ciphertext) = rest.split_at(NONCE_LEN);
+
+    let plaintext = cipher(password, salt)
+        .decrypt(Nonce::from_slice(nonce), ciphertext)
+        .map_err(|_| ChannelError::InvalidState("Wrong password or corrupted state".to_string()))?;
+    serde_json::from_slice(&plaintext).map_err(|e| ChannelError::InvalidState(e.to_string()))
+}
+
+fn cipher(password: &str, salt: &[u8]) -> ChaCha20Poly1305 {
+    let mut key = [0u8; 32];
+    pbkdf2::pbkdf2::<Hmac<Sha256>>(password.as_bytes(), salt, KDF_ROUNDS, &mut key);
+    ChaCha20Poly1305::new(Key::from_slice(&key))
+}
+
+///
+/// Serializable copy of a Streams message
+///
+#[derive(Serialize, Deserialize, Clone)]
+pub(crate) struct StoredMessage {
+    appinst: String,
+    msgid: String,
+    body: String,
+}
+
+impl StoredMessage {
+    ///
+    /// Message tag
+    ///
+    pub(crate) fn msgid(&self) -> &str {
+        &self.msgid
+    }
+
+    ///
+    /// Rebuild the Streams message
+    ///
+    pub(crate) fn to_message(&self) -> Result<Message> {
+        let link = Address::from_str(&self.appinst, &self.msgid)
+            .map_err(|()| ChannelError::InvalidTag(format!("{}:{}", self.appinst, self.msgid)))?;
+        let body = Tbits::from_str(&self.body).ok_or_else(|| {
+            ChannelError::InvalidState(format!("Invalid message body for {}", self.msgid))
+        })?;
+        Ok(Message {
+            link: link,
+            body: body,
+        })
+    }
+}
+
+impl From<&Message> for StoredMessage {
+    fn from(message: &Message) -> Self {
+        Self {
+            appinst: message.link.appinst.to_string(),
+            msgid: message.link.msgid.to_string(),
+            body: message.body.to_string(),
+        }
+    }
+}
@@ -0,0 +1,91 @@
+//!
+//! In-memory transport
+//!
+use super::ChannelTransport;
+use crate::channels_lite::Result;
+use chrono::Utc;
+use iota_streams::app_channels::api::tangle::{Address, Message};
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+type Messages = HashMap<(String, String), Vec<(Message, i64)>>;
+
+///
+/// In-process transport that keeps the messages in a shared map
+///
+/// Clones share the same storage, so an author and its subscribers can
+/// exchange messages without a node by using clones of one instance.
+///
+#[derive(Clone, Default)]
+pub struct InMemoryTransport {
+    messages: Arc<Mutex<Messages>>,
+}
+
+impl InMemoryTransport {
+    ///
+    /// Create an empty transport
+    ///
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    ///
+    /// Number of messages stored so far
+    ///
+    pub fn len(&self) -> usize {
+        self.messages
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .values()
+            .map(|list| list.len())
+            .sum()
+    }
+
+    ///
+    /// Return true if no message has been sent yet
+    ///
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    fn key(link: &Address) -> (String, String) {
+        (link.appinst.to_string(), link.msgid.to_string())
+    }
+}
+
+impl ChannelTransport for InMemoryTransport {
+    fn send_message(&mut self, message: &Message) -> Result<()> {
+        self.messages
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .entry(Self::key(&message.link))
+            .or_insert_with(Vec::new)
+            .push((message.clone(), Utc::now().timestamp_millis()));
+        Ok(())
+    }
+
+    fn recv_messages(&mut self, link: &Address) -> Result<Vec<Message>> {
+        Ok(self
+            .recv_messages_with_timestamp(link)?
+            .into_iter()
+            .map(|(message, _)| message)
+            .collect())
+    }
+
+    fn recv_messages_with_timestamp(
+        &mut self,
+        link: &Address,
+    ) -> Result<Vec<(Message, Option<i64>)>> {
+        Ok(self
+            .messages
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .get(&Self::key(link))
+            .map(|list| {
+                list.iter()
+                    .map(|(message, timestamp)| (message.clone(), Some(*timestamp)))
+                    .collect()
+            })
+            .unwrap_or_default())
+    }
+}
@@ -0,0 +1,108 @@
+//!
+//! Channel transports
+//!
+pub mod memory;
+pub mod pool;
+pub mod tangle;
+
+pub use memory::InMemoryTransport;
+pub use pool::{NodeHealth, NodePool};
+pub use tangle::TangleTransport;
+
+use super::Result;
+use iota_streams::app_channels::api::tangle::{Address, Message};
+use std::time::Duration;
+
+///
+/// Reports kept by a transport until taken, the oldest ones are dropped
+///
+pub const MAX_SEND_REPORTS: usize = 1024;
+
+///
+/// Report of a message sent
+///
+#[derive(Clone, Debug, PartialEq)]
+pub struct SendReport {
+    /// Message tag
+    ///
+    pub msgid: String,
+    /// Node that accepted the message
+    ///
+    pub node: String,
+    /// The PoW was done locally
+    ///
+    pub local_pow: bool,
+    /// Min weight magnitude of the PoW
+    ///
+    pub min_weight_magnitude: u8,
+    /// Time taken by the proof of work alone, local or by the node. None
+    /// when the message was found attached after a send that timed out
+    ///
+    pub pow_duration: Option<Duration>,
+    /// Time taken by the whole send on the node: tips selection, proof of
+    /// work, storage and broadcast
+    ///
+    pub duration: Duration,
+}
+
+///
+/// Transport used by a channel to publish and fetch Streams messages
+///
+/// Both the author and the subscriber own their transport, so channels in the
+/// same process do not share any hidden state and can target different nodes.
+///
+pub trait ChannelTransport {
+    ///
+    /// Send a message to its link
+    ///
+    fn send_message(&mut self, message: &Message) -> Result<()>;
+
+    ///
+    /// Receive all the messages stored at the given link
+    ///
+    fn recv_messages(&mut self, link: &Address) -> Result<Vec<Message>>;
+
+    ///
+    /// Receive all the messages stored at the given link, with their
+    /// attachment timestamp in milliseconds when the transport knows it
+    ///
+    fn recv_messages_with_timestamp(
+        &mut self,
+        link: &Address,
+    ) -> Result<Vec<(Message, Option<i64>)>> {
+        Ok(self
+            .recv_messages(link)?
+            .into_iter()
+            .map(|message| (message, None))
+            .collect())
+    }
+
+    ///
+    /// Reports of the messages sent since the last call, oldest first, when
+    /// the transport keeps them
+    ///
+    fn take_send_reports(&mut self) -> Vec<SendReport> {
+        Vec::new()
+    }
+}
+
+impl<T: ChannelTransport + ?Sized> ChannelTransport for Box<T> {
+    fn send_message(&mut self, message: &Message) -> Result<()> {
+        (**self).send_message(message)
+    }
+
+    fn recv_messages(&mut self, link: &Address) -> Result<Vec<Message>> {
+        (**self).recv_messages(link)
+    }
+
+    fn recv_messages_with_timestamp(
+        &mut self,
+        link: &Address,
+    ) -> Result<Vec<(Message, Option<i64>)>> {
+        (**self).recv_messages_with_timestamp(link)
+    }
+
+    fn take_send_reports(&mut self) -> Vec<SendReport> {
+        (**self).take_send_reports()
+    }
+}
@@ -0,0 +1,427 @@
+//!
+//! Node pool
+//!
+//! Keeps one client per node and probes their health with `getNodeInfo` on a
+//! background thread, so that requests go to the healthiest node first and
+//! fall back on the others.
+//!
+use crate::channels_lite::{ChannelError, Result};
+use iota::client as iota_client;
+use serde_json::{json, Value};
+use std::sync::{mpsc, Arc, Condvar, Mutex, MutexGuard};
+use std::thread;
+use std::time::{Duration, Instant};
+
+// A node may lag one milestone behind while solidifying the latest one
+const MAX_MILESTONE_LAG: i64 = 1;
+// Keeps the background probes from flooding the nodes
+const MIN_HEALTH_CHECK_INTERVAL: Duration = Duration::from_secs(1);
+
+///
+/// Health of a node, as seen by the last probe
+///
+#[derive(Clone, Debug, PartialEq)]
+pub struct NodeHealth {
+    /// Node url
+    ///
+    pub url: String,
+    /// The node answered the probe
+    ///
+    pub reachable: bool,
+    /// The node is synced with the latest milestone
+    ///
+    pub synced: bool,
+    /// Time taken to answer the probe
+    ///
+    pub latency: Option<Duration>,
+}
+
+impl NodeHealth {
+    ///
+    /// Health of a node never probed
+    ///
+    fn unknown(url: &str) -> Self {
+        Self {
+            url: url.to_string(),
+            reachable: true,
+            synced: true,
+            latency: None,
+        }
+    }
+
+    ///
+    /// Rank of the node, lower is better
+    ///
+    fn rank(&self) -> (u8, Duration) {
+        let state = match (self.reachable, self.synced) {
+            (true, true) => 0,
+            (true, false) => 1,
+            (false, _) => 2,
+        };
+        (state, self.latency.unwrap_or(Duration::from_secs(0)))
+    }
+}
+
+///
+/// Health of the nodes, updated by the background probes
+///
+struct Monitor {
+    health: Mutex<Vec<NodeHealth>>,
+    shutdown: Mutex<bool>,
+    wakeup: Condvar,
+}
+
+impl Monitor {
+    fn health(&self) -> MutexGuard<'_, Vec<NodeHealth>> {
+        self.health.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    ///
+    /// Probe every node now, in parallel
+    ///
+    fn check(&self, timeout: Duration) {
+        let urls: Vec<String> = self.health().iter().map(|node| node.url.clone()).collect();
+        let probes: Vec<_> = urls
+            .iter()
+            .map(|url| {
+                let url = url.clone();
+                thread::spawn(move || probe(&url, timeout))
+            })
+            .collect();
+
+        let health = urls
+            .iter()
+            .zip(probes)
+            .map(|(url, probe)| {
+                probe.join().unwrap_or_else(|_| NodeHealth {
+                    reachable: false,
+                    synced: false,
+                    ..NodeHealth::unknown(url)
+                })
+            })
+            .collect();
+        *self.health() = health;
+    }
+}
+
+///
+/// Probe the nodes every interval, until the pool is dropped
+///
+fn watch(monitor: Arc<Monitor>, interval: Duration, timeout: Duration) {
+    loop {
+        monitor.check(timeout);
+
+        let next_check = Instant::now() + interval;
+        let mut shutdown = monitor.shutdown.lock().unwrap_or_else(|e| e.into_inner());
+        while !*shutdown && Instant::now() < next_check {
+            shutdown = monitor
+                .wakeup
+                .wait_timeout(
+                    shutdown,
+                    next_check.saturating_duration_since(Instant::now()),
+                )
+                .map(|(shutdown, _)| shutdown)
+                .unwrap_or_else(|e| e.into_inner().0);
+        }
+        if *shutdown {
+            return;
+        }
+    }
+}
+
+///
+/// Why a request on a node failed
+///
+enum Failure {
+    TimedOut(Duration),
+    Failed(ChannelError),
+}
+
+impl Failure {
+    fn into_error(self, url: &str) -> ChannelError {
+        match self {
+            Self::TimedOut(timeout) => ChannelError::Transport(format!(
+                "{}: No answer from the node after {:?}",
+                url, timeout
+            )),
+            Self::Failed(e) => ChannelError::Transport(format!("{}: {}", url, e)),
+        }
+    }
+}
+
+///
+/// Pool of nodes ordered by health
+///
+/// Nodes are probed by a background thread every health check interval, so
+/// requests never wait for the probes. A node failing a request is ranked
+/// last until the next probe.
+///
+pub struct NodePool {
+    clients: Vec<Arc<Mutex<iota_client::Client>>>,
+    monitor: Arc<Monitor>,
+    probe_timeout: Duration,
+}
+
+impl NodePool {
+    ///
+    /// Create a pool and start probing the nodes in the background
+    ///
+    pub fn new(urls: &[String], interval: Duration, probe_timeout: Duration) -> Result<Self> {
+        if urls.is_empty() {
+            return Err(ChannelError::Transport("No node configured".to_string()));
+        }
+
+        let clients = urls
+            .iter()
+            .map(|url| {
+                iota_client::ClientBuilder::new()
+                    .node(url)
+                    .and_then(|builder| builder.build())
+                    .map(|client| Arc::new(Mutex::new(client)))
+                    .map_err(|e| ChannelError::Transport(format!("{}: {}", url, e)))
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        let monitor = Arc::new(Monitor {
+            health: Mutex::new(urls.iter().map(|url| NodeHealth::unknown(url)).collect()),
+            shutdown: Mutex::new(false),
+            wakeup: Condvar::new(),
+        });
+        {
+            let monitor = monitor.clone();
+            let interval = interval.max(MIN_HEALTH_CHECK_INTERVAL);
+            thread::spawn(move || watch(monitor, interval, probe_timeout));
+        }
+
+        Ok(Self {
+            clients: clients,
+            monitor: monitor,
+            probe_timeout: probe_timeout,
+        })
+    }
+
+    ///
+    /// Health of the nodes, as seen by the last probe
+    ///
+    pub fn health(&self) -> Vec<NodeHealth> {
+        self.monitor.health().clone()
+    }
+
+    ///
+    /// Probe every node now, in parallel, instead of waiting for the
+    /// background probes
+    ///
+    pub fn check_health(&mut self) {
+        self.monitor.check(self.probe_timeout);
+    }
+
+    ///
+    /// Run the request on the healthiest node, then on the next ones until
+    /// one succeeds. Returns the result with the url of the node.
+    ///
+    pub(crate) fn request<F, R>(
+        &mut self,
+        timeout: Option<Duration>,
+        request: F,
+    ) -> Result<(R, String)>
+    where
+        F: Fn(&mut iota_client::Client) -> Result<R> + Send + Sync + 'static,
+        R: Send + 'static,
+    {
+        let request = Arc::new(request);
+        let mut last_error = None;
+        for (index, url) in self.order() {
+            match self.run(index, timeout, request.clone()) {
+                Ok(result) => return Ok((result, url)),
+                Err(failure) => {
+                    self.set_unreachable(index);
+                    last_error = Some(failure.into_error(&url));
+                }
+            }
+        }
+        Err(last_error.unwrap_or_else(|| ChannelError::Transport("No node available".to_string())))
+    }
+
+    ///
+    /// Send on the healthiest node, then on the next ones until one succeeds
+    ///
+    /// A node that timed out may still attach the message, so once a send
+    /// timed out, in this call or a previous one as told by `unconfirmed`,
+    /// each node is asked whether the message `landed` before sending there,
+    /// and skipped when it can not tell. `unconfirmed` is left set when the
+    /// message may have been sent. Returns None instead of the send result
+    /// when the message was found attached.
+    ///
+    pub(crate) fn send<F, C, R>(
+        &mut self,
+        timeout: Option<Duration>,
+        check_timeout: Option<Duration>,
+        unconfirmed: &mut bool,
+        send: F,
+        landed: C,
+    ) -> Result<(Option<R>, String)>
+    where
+        F: Fn(&mut iota_client::Client) -> Result<R> + Send + Sync + 'static,
+        C: Fn(&mut iota_client::Client) -> Result<bool> + Send + Sync + 'static,
+        R: Send + 'static,
+    {
+        let send = Arc::new(send);
+        let landed = Arc::new(landed);
+        let mut last_error = None;
+        for (index, url) in self.order() {
+            if *unconfirmed {
+                match self.run(index, check_timeout, landed.clone()) {
+                    Ok(true) => {
+                        *unconfirmed = false;
+                        return Ok((None, url));
+                    }
+                    Ok(false) => {}
+                    Err(failure) => {
+                        self.set_unreachable(index);
+                        last_error = Some(failure.into_error(&url));
+                        continue;
+                    }
+                }
+            }
+
+            match self.run(index, timeout, send.clone()) {
+                Ok(result) => {
+                    *unconfirmed = false;
+                    return Ok((Some(result), url));
+                }
+                Err(failure) => {
+                    if let Failure::TimedOut(_) = failure {
+                        *unconfirmed = true;
+                    }
+                    self.set_unreachable(index);
+                    last_error = Some(failure.into_error(&url));
+                }
+            }
+        }
+        Err(last_error.unwrap_or_else(|| ChannelError::Transport("No node available".to_string())))
+    }
+
+    ///
+    /// Indexes and urls of the nodes, the healthiest first
+    ///
+    fn order(&self) -> Vec<(usize, String)> {
+        let health = self.monitor.health();
+        let mut order: Vec<usize> = (0..health.len()).collect();
+        order.sort_by_key(|&index| health[index].rank());
+        order
+            .into_iter()
+            .map(|index| (index, health[index].url.clone()))
+            .collect()
+    }
+
+    fn set_unreachable(&self, index: usize) {
+        self.monitor.health()[index].reachable = false;
+    }
+
+    fn run<F, R>(
+        &self,
+        index: usize,
+        timeout: Option<Duration>,
+        request: Arc<F>,
+    ) -> std::result::Result<R, Failure>
+    where
+        F: Fn(&mut iota_client::Client) -> Result<R> + Send + Sync + 'static,
+        R: Send + 'static,
+    {
+        let client = self.clients[index].clone();
+        with_timeout(timeout, move || {
+            let mut client = client
+                .lock()
+                .map_err(|_| ChannelError::Transport("Client lock poisoned".to_string()))?;
+            request(&mut client)
+        })
+    }
+}
+
+impl Drop for NodePool {
+    fn drop(&mut self) {
+        *self
+            .monitor
+            .shutdown
+            .lock()
+            .unwrap_or_else(|e| e.into_inner()) = true;
+        self.monitor.wakeup.notify_all();
+    }
+}
+
+///
+/// Run the request in the current thread, or in a new one when it has to
+/// be given up after a timeout
+///
+/// The request keeps running in the background once timed out, its result
+/// is dropped.
+///
+fn with_timeout<F, R>(timeout: Option<Duration>, request: F) -> std::result::Result<R, Failure>
+where
+    F: FnOnce() -> Result<R> + Send + 'static,
+    R: Send + 'static,
+{
+    match timeout {
+        None => request().map_err(Failure::Failed),
+        Some(timeout) => {
+            let (sender, receiver) = mpsc::channel();
+            thread::spawn(move || {
+                let _ = sender.send(request());
+            });
+            receiver
+                .recv_timeout(timeout)
+                .map_err(|_| Failure::TimedOut(timeout))?
+                .map_err(Failure::Failed)
+        }
+    }
+}
+
+///
+/// Ask the node for its info and measure the time it takes
+///
+/// Runs outside of any tokio runtime, as required by the blocking client.
+///
+fn probe(url: &str, timeout: Duration) -> NodeHealth {
+    let start = Instant::now();
+    match call(url, &json!({ "command": "getNodeInfo" }), timeout) {
+        Ok(info) => {
+            let latest = info["latestMilestoneIndex"].as_i64();
+            let solid = info["latestSolidSubtangleMilestoneIndex"].as_i64();
+            NodeHealth {
+                url: url.to_string(),
+                reachable: true,
+                synced: match (latest, solid) {
+                    (Some(latest), Some(solid)) => latest - solid <= MAX_MILESTONE_LAG,
+                    _ => false,
+                },
+                latency: Some(start.elapsed()),
+            }
+        }
+        Err(_) => NodeHealth {
+            url: url.to_string(),
+            reachable: false,
+            synced: false,
+            latency: None,
+        },
+    }
+}
+
+///
+/// Send a command to the node API with the blocking client
+///
+/// Must run outside of any tokio runtime.
+///
+pub(crate) fn call(url: &str, command: &Value, timeout: Duration) -> reqwest::Result<Value> {
+    reqwest::blocking::Client::builder()
+        .timeout(timeout)
+        .build()
+        .and_then(|client| {
+            client
+                .post(url)
+                .header("X-IOTA-API-Version", "1")
+                .json(command)
+                .send()
+        })
+        .and_then(|response| response.error_for_status())
+        .and_then(|response| response.json::<Value>())
+}
@@ -0,0 +1,269 @@
+//!
+//! Tangle transport
+//!
+use super::pool::{self, NodeHealth, NodePool};
+use super::{ChannelTransport, SendReport, MAX_SEND_REPORTS};
+use crate::channels_lite::{ChannelError, NetworkConfig, Result, Timeouts};
+use chrono::Utc;
+use futures::executor::block_on;
+use iota::client as iota_client;
+use iota::crypto::ternary::Hash;
+use iota_streams::app::transport::tangle::client::{msg_to_tangle, RecvOptions, SendTrytesOptions};
+use iota_streams::app::transport::Transport;
+use iota_streams::app_channels::api::tangle::{Address, Message};
+use serde_json::json;
+use std::collections::VecDeque;
+use std::thread;
+use std::time::{Duration, Instant};
+
+// Tryte range of the attachment timestamp in the transaction trytes
+const ATTACHMENT_TIMESTAMP: (usize, usize) = (2619, 2628);
+
+///
+/// Transport that publishes the messages on the Tangle through IOTA nodes
+///
+/// Each request goes to the healthiest node of the configuration and is
+/// retried on the other ones when it fails. A send that timed out is only
+/// tried again on a node that does not have the message, so that it is never
+/// attached twice.
+///
+pub struct TangleTransport {
+    pool: NodePool,
+    send_opt: SendTrytesOptions,
+    timeouts: Timeouts,
+    reports: VecDeque<SendReport>,
+    unconfirmed: Option<String>,
+}
+
+impl TangleTransport {
+    ///
+    /// Create a transport bound to the given network
+    ///
+    pub fn new<N: Into<NetworkConfig>>(node: N) -> Result<Self> {
+        let config = node.into();
+        let pool = NodePool::new(
+            &config.nodes,
+            config.health_check_interval(),
+            config.timeouts.probe(),
+        )?;
+
+        Ok(Self {
+            pool: pool,
+            send_opt: config.send_options(),
+            timeouts: config.timeouts,
+            reports: VecDeque::new(),
+            unconfirmed: None,
+        })
+    }
+
+    ///
+    /// Health of the nodes, as seen by the last probe
+    ///
+    pub fn node_health(&self) -> Vec<NodeHealth> {
+        self.pool.health()
+    }
+
+    ///
+    /// Probe the nodes now instead of waiting for the next health check
+    ///
+    pub fn check_health(&mut self) -> Vec<NodeHealth> {
+        self.pool.check_health();
+        self.pool.health()
+    }
+}
+
+impl TangleTransport {
+    fn recv(&mut self, link: &Address) -> Result<(Vec<Message>, String)> {
+        let link = link.clone();
+        self.pool.request(self.timeouts.recv(), move |client| {
+            client
+                .recv_messages_with_options(&link, RecvOptions::default())
+                .map_err(|e| ChannelError::Transport(e.to_string()))
+        })
+    }
+}
+
+impl ChannelTransport for TangleTransport {
+    fn send_message(&mut self, message: &Message) -> Result<()> {
+        let msgid = message.link.msgid.to_string();
+        let link = message.link.clone();
+        let message = message.clone();
+        let send_opt = self.send_opt;
+        let start = Instant::now();
+
+        let mut unconfirmed = self.unconfirmed.as_ref() == Some(&msgid);
+        let sent = self.pool.send(
+            self.timeouts.send(),
+            self.timeouts.recv(),
+            &mut unconfirmed,
+            move |client| {
+                let start = Instant::now();
+                let pow_duration = send_bundle(client, &message, send_opt)?;
+                Ok((pow_duration, start.elapsed()))
+            },
+            move |client| {
+                client
+                    .recv_messages_with_options(&link, RecvOptions::default())
+                    .map(|messages| !messages.is_empty())
+                    .map_err(|e| ChannelError::Transport(e.to_string()))
+            },
+        );
+        self.unconfirmed = if unconfirmed {
+            Some(msgid.clone())
+        } else {
+            None
+        };
+        let (durations, node) = sent?;
+
+        if self.reports.len() == MAX_SEND_REPORTS {
+            self.reports.pop_front();
+        }
+        self.reports.push_back(SendReport {
+            msgid: msgid,
+            node: node,
+            local_pow: self.send_opt.local_pow,
+            min_weight_magnitude: self.send_opt.min_weight_magnitude,
+            pow_duration: durations.map(|(pow_duration, _)| pow_duration),
+            duration: durations.map_or_else(|| start.elapsed(), |(_, duration)| duration),
+        });
+        Ok(())
+    }
+
+    fn recv_messages(&mut self, link: &Address) -> Result<Vec<Message>> {
+        self.recv(link).map(|(messages, _node)| messages)
+    }
+
+    ///
+    /// The timestamp is the earliest attachment timestamp of the transactions
+    /// found at the link, as stored by the node that returned the messages
+    ///
+    fn recv_messages_with_timestamp(
+        &mut self,
+        link: &Address,
+    ) -> Result<Vec<(Message, Option<i64>)>> {
+        let (messages, node) = self.recv(link)?;
+        if messages.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let timestamp = attachment_timestamp(
+            &node,
+            link,
+            self.timeouts.recv().unwrap_or(self.timeouts.probe()),
+        );
+        Ok(messages
+            .into_iter()
+            .map(|message| (message, timestamp))
+            .collect())
+    }
+
+    fn take_send_reports(&mut self) -> Vec<SendReport> {
+        self.reports.drain(..).collect()
+    }
+}
+
+///
+/// Wrap the message in a bundle, attach it and broadcast it, returns the time
+/// taken by the attachment alone, which is the proof of work
+///
+fn send_bundle(
+    client: &mut iota_client::Client,
+    message: &Message,
+    send_opt: SendTrytesOptions,
+) -> Result<Duration> {
+    let transactions = msg_to_tangle(
+        message,
+        Utc::now().timestamp() as u64,
+        Hash::zeros(),
+        Hash::zeros(),
+    )
+    .map_err(|e| ChannelError::Transport(e.to_string()))?;
+
+    block_on(async {
+        let tips = client
+            .get_transactions_to_approve()
+            .depth(send_opt.depth)
+            .send()
+            .await
+            .map_err(|e| ChannelError::Transport(e.to_string()))?;
+
+        let start = Instant::now();
+        let attached = client
+            .attach_to_tangle()
+            .trunk_transaction(&tips.trunk_transaction)
+            .branch_transaction(&tips.branch_transaction)
+            .min_weight_magnitude(send_opt.min_weight_magnitude)
+            .local_pow(send_opt.local_pow)
+            .threads(send_opt.threads)
+            .trytes(&transactions)
+            .send()
+            .await
+            .map_err(|e| ChannelError::Transport(e.to_string()))?;
+        let pow_duration = start.elapsed();
+
+        client
+            .store_and_broadcast(&attached.trytes)
+            .await
+            .map_err(|e| ChannelError::Transport(e.to_string()))?;
+        Ok(pow_duration)
+    })
+}
+
+///
+/// Earliest attachment timestamp of the transactions at the link, in
+/// milliseconds, None when the node can not tell
+///
+/// The blocking client runs in its own thread, away from any tokio runtime.
+///
+fn attachment_timestamp(url: &str, link: &Address, timeout: Duration) -> Option<i64> {
+    let url = url.to_string();
+    let address = link.appinst.to_string();
+    let tag = link.msgid.to_string();
+    thread::spawn(move || {
+        let found = pool::call(
+            &url,
+            &json!({
+                "command": "findTransactions",
+                "addresses": [address],
+                "tags": [tag],
+            }),
+            timeout,
+        )
+        .ok()?;
+        let trytes = pool::call(
+            &url,
+            &json!({ "command": "getTrytes", "hashes": found["hashes"] }),
+            timeout,
+        )
+        .ok()?;
+
+        trytes["trytes"]
+            .as_array()?
+            .iter()
+            .filter_map(|tx| {
+                tx.as_str()?
+                    .get(ATTACHMENT_TIMESTAMP.0..ATTACHMENT_TIMESTAMP.1)
+            })
+            .filter_map(trytes_value)
+            .filter(|timestamp| *timestamp > 0)
+            .min()
+    })
+    .join()
+    .ok()
+    .flatten()
+}
+
+///
+/// Value of a balanced ternary number written in trytes, least significant first
+///
+fn trytes_value(trytes: &str) -> Option<i64> {
+    trytes.bytes().rev().try_fold(0i64, |value, tryte| {
+        let digit = match tryte {
+            b'9' => 0,
+            b'A'..=b'M' => (tryte - b'A') as i64 + 1,
+            b'N'..=b'Z' => (tryte - b'N') as i64 - 13,
+            _ => return None,
+        };
+        Some(value * 27 + digit)
+    })
+}
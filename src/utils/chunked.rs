@@ -0,0 +1,172 @@
+//!
+//! Chunked payloads
+//!
+//! Blobs too large for a single message are split across several packets.
+//! Each chunk carries the SHA-256 of the whole blob, its index and the
+//! number of chunks, so the subscriber can reassemble and check the blob.
+//!
+use crate::channels_lite::channel_subscriber::ReceivedMessage;
+use crate::channels_lite::{ChannelError, Result};
+use crate::utils::payload::{json, PayloadEncoding, MAX_PAYLOAD_SIZE};
+use base64::{decode_config, encode_config, URL_SAFE_NO_PAD};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::{BTreeMap, HashMap};
+
+// Most chunks accepted for a single blob
+const MAX_CHUNKS: u32 = 10_000;
+// Room left in a message for the chunk header, once encoded
+const HEADER_SIZE: usize = 400;
+const MIN_CHUNK_SIZE: usize = 16;
+
+///
+/// Part of a blob, sent as the public or masked data of a packet
+///
+#[derive(Serialize, Deserialize, Debug, Clone)]
+struct Chunk {
+    chunk_of: String,
+    index: u32,
+    count: u32,
+    data: String,
+}
+
+///
+/// Split the blob into payloads fitting in a message, the chunks go in the
+/// masked data when `masked`, in the public data otherwise
+///
+pub fn payloads(blob: &[u8], masked: bool) -> Result<Vec<json::Payload>> {
+    let id = format!("{:x}", Sha256::digest(blob));
+
+    // Chunks use the binary encoding, data is only inflated by base64
+    let mut chunk_size =
+        ((MAX_PAYLOAD_SIZE.saturating_sub(HEADER_SIZE)) * 3 / 4).max(MIN_CHUNK_SIZE);
+    loop {
+        match build(&id, blob, chunk_size, masked) {
+            Err(ChannelError::PayloadTooLarge { .. }) if chunk_size > MIN_CHUNK_SIZE => {
+                chunk_size = (chunk_size * 9 / 10).max(MIN_CHUNK_SIZE);
+            }
+            result => return result,
+        }
+    }
+}
+
+fn build(id: &str, blob: &[u8], chunk_size: usize, masked: bool) -> Result<Vec<json::Payload>> {
+    let parts: Vec<&[u8]> = if blob.is_empty() {
+        vec![blob]
+    } else {
+        blob.chunks(chunk_size).collect()
+    };
+    if parts.len() > MAX_CHUNKS as usize {
+        return Err(ChannelError::PayloadTooLarge {
+            size: blob.len(),
+            max: MAX_CHUNKS as usize * chunk_size,
+        });
+    }
+
+    let count = parts.len() as u32;
+    parts
+        .iter()
+        .enumerate()
+        .map(|(index, part)| {
+            let chunk = Chunk {
+                chunk_of: id.to_string(),
+                index: index as u32,
+                count: count,
+                data: encode_config(part, URL_SAFE_NO_PAD),
+            };
+            let mut builder = json::PayloadBuilder::with_encoding(PayloadEncoding::Binary);
+            if masked {
+                builder.masked(&chunk)?;
+            } else {
+                builder.public(&chunk)?;
+            }
+            Ok(builder.build())
+        })
+        .collect()
+}
+
+struct PartialBlob {
+    count: u32,
+    parts: BTreeMap<u32, Vec<u8>>,
+}
+
+///
+/// Reassemble the blobs from the chunks read on the channel
+///
+/// Messages that are not chunks are ignored, chunks can arrive in any order
+/// and more than once.
+///
+#[derive(Default)]
+pub struct ChunkAssembler {
+    blobs: HashMap<String, PartialBlob>,
+}
+
+impl ChunkAssembler {
+    ///
+    /// Create an empty assembler
+    ///
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    ///
+    /// Add the chunk held by the message, returns the blob once complete
+    /// and checked
+    ///
+    pub fn push(&mut self, message: &ReceivedMessage) -> Result<Option<Vec<u8>>> {
+        let chunk = match message
+            .masked
+            .iter()
+            .chain(message.public.iter())
+            .find_map(|data| serde_json::from_str::<Chunk>(data).ok())
+        {
+            Some(chunk) => chunk,
+            None => return Ok(None),
+        };
+        if chunk.count == 0 || chunk.count > MAX_CHUNKS || chunk.index >= chunk.count {
+            return Err(ChannelError::MalformedPayload(format!(
+                "Invalid chunk {} of {}",
+                chunk.index, chunk.count
+            )));
+        }
+        let data = decode_config(&chunk.data, URL_SAFE_NO_PAD)
+            .map_err(|e| ChannelError::MalformedPayload(e.to_string()))?;
+
+        let blob = self
+            .blobs
+            .entry(chunk.chunk_of.clone())
+            .or_insert_with(|| PartialBlob {
+                count: chunk.count,
+                parts: BTreeMap::new(),
+            });
+        if blob.count != chunk.count {
+            return Err(ChannelError::MalformedPayload(format!(
+                "Chunk count {} does not match {}",
+                chunk.count, blob.count
+            )));
+        }
+        blob.parts.insert(chunk.index, data);
+        if blob.parts.len() < blob.count as usize {
+            return Ok(None);
+        }
+
+        let blob = match self.blobs.remove(&chunk.chunk_of) {
+            Some(blob) => blob,
+            None => return Ok(None),
+        };
+        let data: Vec<u8> = blob.parts.into_values().flatten().collect();
+        if format!("{:x}", Sha256::digest(&data)) != chunk.chunk_of {
+            return Err(ChannelError::MalformedPayload(
+                "Chunked payload integrity check failed".to_string(),
+            ));
+        }
+        Ok(Some(data))
+    }
+
+    ///
+    /// Number of chunks received for the blobs not complete yet
+    ///
+    pub fn pending_chunks(&self) -> usize {
+        self.blobs.values().map(|blob| blob.parts.len()).sum()
+    }
+}
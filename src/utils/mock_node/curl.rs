@@ -0,0 +1,84 @@
+//!
+//! Curl-P-81 hashing and ternary helpers used by the mock node
+//!
+const TRYTE_ALPHABET: &[u8] = b"9ABCDEFGHIJKLMNOPQRSTUVWXYZ";
+const TRUTH_TABLE: [i8; 11] = [1, 0, -1, 2, 1, -1, 0, 2, -1, 1, 0];
+const STATE_LENGTH: usize = 729;
+const HASH_LENGTH: usize = 243;
+const ROUNDS: usize = 81;
+
+///
+/// Convert a tryte string into trits, returns None on invalid trytes
+///
+pub fn trytes_to_trits(trytes: &str) -> Option<Vec<i8>> {
+    let mut trits = Vec::with_capacity(trytes.len() * 3);
+    for c in trytes.bytes() {
+        let value = TRYTE_ALPHABET.iter().position(|t| *t == c)? as i8;
+        let value = if value > 13 { value - 27 } else { value };
+        trits.extend_from_slice(&int_to_trits(value as i64, 3));
+    }
+    Some(trits)
+}
+
+///
+/// Convert trits into a tryte string
+///
+pub fn trits_to_trytes(trits: &[i8]) -> String {
+    trits
+        .chunks(3)
+        .map(|chunk| {
+            let value = chunk.iter().rev().fold(0i8, |acc, trit| acc * 3 + trit);
+            let index = if value < 0 { value + 27 } else { value };
+            TRYTE_ALPHABET[index as usize] as char
+        })
+        .collect()
+}
+
+///
+/// Balanced ternary representation of a number on `length` trits
+///
+pub fn int_to_trits(mut value: i64, length: usize) -> Vec<i8> {
+    let mut trits = vec![0i8; length];
+    for trit in trits.iter_mut() {
+        let mut remainder = value % 3;
+        value /= 3;
+        if remainder > 1 {
+            remainder -= 3;
+            value += 1;
+        } else if remainder < -1 {
+            remainder += 3;
+            value -= 1;
+        }
+        *trit = remainder as i8;
+    }
+    trits
+}
+
+///
+/// Curl-P-81 hash of the given trits, returned as trytes
+///
+pub fn hash(trits: &[i8]) -> String {
+    let mut state = [0i8; STATE_LENGTH];
+    for chunk in trits.chunks(HASH_LENGTH) {
+        state[..chunk.len()].copy_from_slice(chunk);
+        transform(&mut state);
+    }
+    trits_to_trytes(&state[..HASH_LENGTH])
+}
+
+fn transform(state: &mut [i8; STATE_LENGTH]) {
+    let mut scratch = [0i8; STATE_LENGTH];
+    let mut index = 0;
+    for _ in 0..ROUNDS {
+        scratch.copy_from_slice(state);
+        for trit in state.iter_mut() {
+            let previous = index;
+            if index < 365 {
+                index += 364;
+            } else {
+                index -= 365;
+            }
+            *trit = TRUTH_TABLE[(scratch[previous] + (scratch[index] << 2) + 5) as usize];
+        }
+    }
+}
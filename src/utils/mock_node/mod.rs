@@ -0,0 +1,446 @@
+//!
+//! Mock IRI/Hornet node
+//!
+//! Minimal HTTP server implementing the subset of the node API used by the
+//! iota client, so that `NetworkConfig::custom(node.url(), 9)` can be used to
+//! exercise the real HTTP code path without internet access.
+//!
+//! Proof of work is not performed nor validated: `attachToTangle` only fills
+//! the trunk, branch, timestamps and hash of each transaction.
+//!
+//! Several nodes can share the same Tangle with `peer`, and be switched
+//! offline or out of sync to exercise the node failover.
+//!
+mod curl;
+
+use serde_json::{json, Value};
+use std::collections::{HashMap, HashSet};
+use std::io::{self, BufRead, BufReader, Read, Write};
+use std::net::{SocketAddr, TcpListener, TcpStream, ToSocketAddrs};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+const TRANSACTION_TRYTES: usize = 2673;
+const NULL_HASH: &str =
+    "999999999999999999999999999999999999999999999999999999999999999999999999999999999";
+const MAX_TIMESTAMP_VALUE: i64 = (3i64.pow(27) - 1) / 2;
+const LATEST_MILESTONE_INDEX: i64 = 1000;
+const UNSYNCED_LAG: i64 = 10;
+
+// Tryte offsets of the transaction fields
+const ADDRESS: (usize, usize) = (2187, 2268);
+const OBSOLETE_TAG: (usize, usize) = (2295, 2322);
+const BUNDLE: (usize, usize) = (2349, 2430);
+const TRUNK: (usize, usize) = (2430, 2511);
+const BRANCH: (usize, usize) = (2511, 2592);
+const TAG: (usize, usize) = (2592, 2619);
+const NONCE: (usize, usize) = (2646, 2673);
+
+type Index = HashMap<String, Vec<String>>;
+
+///
+/// Transactions known by the mock node
+///
+#[derive(Default)]
+struct Tangle {
+    trytes: HashMap<String, String>,
+    addresses: Index,
+    tags: Index,
+    bundles: Index,
+    approvees: Index,
+    latest: Option<String>,
+}
+
+impl Tangle {
+    fn store(&mut self, trytes: &str) -> Result<String, String> {
+        let hash = transaction_hash(trytes)?;
+        if self.trytes.contains_key(&hash) {
+            return Ok(hash);
+        }
+
+        let field = |(start, end): (usize, usize)| trytes[start..end].to_string();
+        let index = |map: &mut Index, key: String| map.entry(key).or_default().push(hash.clone());
+        index(&mut self.addresses, field(ADDRESS));
+        index(&mut self.tags, field(TAG));
+        if field(OBSOLETE_TAG) != field(TAG) {
+            index(&mut self.tags, field(OBSOLETE_TAG));
+        }
+        index(&mut self.bundles, field(BUNDLE));
+        index(&mut self.approvees, field(TRUNK));
+        if field(BRANCH) != field(TRUNK) {
+            index(&mut self.approvees, field(BRANCH));
+        }
+
+        self.trytes.insert(hash.clone(), trytes.to_string());
+        self.latest = Some(hash.clone());
+        Ok(hash)
+    }
+
+    fn find(&self, filters: &[(&Index, Vec<String>, usize)]) -> Vec<String> {
+        let mut result: Option<HashSet<String>> = None;
+        for (map, keys, length) in filters.iter() {
+            if keys.is_empty() {
+                continue;
+            }
+            let found: HashSet<String> = keys
+                .iter()
+                .filter_map(|key| map.get(&pad(key, *length)))
+                .flatten()
+                .cloned()
+                .collect();
+            result = Some(match result {
+                Some(previous) => previous.intersection(&found).cloned().collect(),
+                None => found,
+            });
+        }
+        result.unwrap_or_default().into_iter().collect()
+    }
+}
+
+///
+/// Mock node running in a background thread
+///
+pub struct MockNode {
+    address: SocketAddr,
+    tangle: Arc<Mutex<Tangle>>,
+    status: Arc<Status>,
+    handle: Option<JoinHandle<()>>,
+}
+
+///
+/// Switches shared with the server thread
+///
+struct Status {
+    shutdown: AtomicBool,
+    online: AtomicBool,
+    synced: AtomicBool,
+}
+
+impl MockNode {
+    ///
+    /// Start a node on a random local port
+    ///
+    pub fn start() -> io::Result<Self> {
+        Self::bind("127.0.0.1:0")
+    }
+
+    ///
+    /// Start a node on the given address
+    ///
+    pub fn bind<A: ToSocketAddrs>(address: A) -> io::Result<Self> {
+        Self::bind_with_tangle(address, Arc::new(Mutex::new(Tangle::default())))
+    }
+
+    ///
+    /// Start another node on a random local port, sharing the Tangle of
+    /// this one as if they were neighbors
+    ///
+    pub fn peer(&self) -> io::Result<Self> {
+        Self::bind_with_tangle("127.0.0.1:0", self.tangle.clone())
+    }
+
+    fn bind_with_tangle<A: ToSocketAddrs>(
+        address: A,
+        tangle: Arc<Mutex<Tangle>>,
+    ) -> io::Result<Self> {
+        let listener = TcpListener::bind(address)?;
+        listener.set_nonblocking(true)?;
+        let address = listener.local_addr()?;
+
+        let status = Arc::new(Status {
+            shutdown: AtomicBool::new(false),
+            online: AtomicBool::new(true),
+            synced: AtomicBool::new(true),
+        });
+
+        let handle = {
+            let tangle = tangle.clone();
+            let status = status.clone();
+            thread::spawn(move || serve(listener, tangle, status))
+        };
+
+        Ok(Self {
+            address: address,
+            tangle: tangle,
+            status: status,
+            handle: Some(handle),
+        })
+    }
+
+    ///
+    /// Url of the node, to be used in `NetworkConfig::custom`
+    ///
+    pub fn url(&self) -> String {
+        format!("http://{}", self.address)
+    }
+
+    ///
+    /// Number of transactions stored by the node
+    ///
+    pub fn transactions_count(&self) -> usize {
+        self.tangle.lock().unwrap().trytes.len()
+    }
+
+    ///
+    /// Switch the node offline, every request is answered with an error
+    /// until it is switched back online
+    ///
+    pub fn set_online(&self, online: bool) {
+        self.status.online.store(online, Ordering::SeqCst);
+    }
+
+    ///
+    /// Make the node report its solid milestone behind the latest one
+    ///
+    pub fn set_synced(&self, synced: bool) {
+        self.status.synced.store(synced, Ordering::SeqCst);
+    }
+
+    ///
+    /// Stop the node
+    ///
+    pub fn stop(&mut self) {
+        self.status.shutdown.store(true, Ordering::SeqCst);
+        if let Some(handle) = self.handle.take() {
+            let _ = handle.join();
+        }
+    }
+}
+
+impl Drop for MockNode {
+    fn drop(&mut self) {
+        self.stop();
+    }
+}
+
+fn serve(listener: TcpListener, tangle: Arc<Mutex<Tangle>>, status: Arc<Status>) {
+    while !status.shutdown.load(Ordering::SeqCst) {
+        match listener.accept() {
+            Ok((stream, _)) => {
+                let tangle = tangle.clone();
+                let status = status.clone();
+                thread::spawn(move || {
+                    let _ = handle_connection(stream, &tangle, &status);
+                });
+            }
+            Err(ref e) if e.kind() == io::ErrorKind::WouldBlock => {
+                thread::sleep(Duration::from_millis(10));
+            }
+            Err(_) => break,
+        }
+    }
+}
+
+fn handle_connection(stream: TcpStream, tangle: &Mutex<Tangle>, status: &Status) -> io::Result<()> {
+    stream.set_nonblocking(false)?;
+    let mut reader = BufReader::new(stream.try_clone()?);
+
+    let mut content_length = 0;
+    let mut line = String::new();
+    loop {
+        line.clear();
+        if reader.read_line(&mut line)? == 0 {
+            return Ok(());
+        }
+        let header = line.trim_end();
+        if header.is_empty() {
+            break;
+        }
+        let mut parts = header.splitn(2, ':');
+        if let (Some(name), Some(value)) = (parts.next(), parts.next()) {
+            if name.trim().eq_ignore_ascii_case("content-length") {
+                content_length = value.trim().parse().unwrap_or(0);
+            }
+        }
+    }
+
+    let mut body = vec![0u8; content_length];
+    reader.read_exact(&mut body)?;
+
+    let synced = status.synced.load(Ordering::SeqCst);
+    let (code, response) = if !status.online.load(Ordering::SeqCst) {
+        (
+            "503 Service Unavailable",
+            json!({ "error": "Node is offline" }),
+        )
+    } else {
+        match serde_json::from_slice::<Value>(&body) {
+            Ok(request) => match dispatch(&request, &mut tangle.lock().unwrap(), synced) {
+                Ok(response) => ("200 OK", response),
+                Err(error) => ("400 Bad Request", json!({ "error": error })),
+            },
+            Err(e) => ("400 Bad Request", json!({ "error": e.to_string() })),
+        }
+    };
+
+    let response = response.to_string();
+    let mut stream = stream;
+    write!(
+        stream,
+        "HTTP/1.1 {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+        code,
+        response.len(),
+        response
+    )?;
+    stream.flush()
+}
+
+fn dispatch(request: &Value, tangle: &mut Tangle, synced: bool) -> Result<Value, String> {
+    let command = request["command"]
+        .as_str()
+        .ok_or_else(|| "Missing command".to_string())?;
+
+    match command {
+        "getNodeInfo" => Ok(json!({
+            "appName": "channels-lite-mock-node",
+            "appVersion": env!("CARGO_PKG_VERSION"),
+            "latestMilestone": NULL_HASH,
+            "latestMilestoneIndex": LATEST_MILESTONE_INDEX,
+            "latestSolidSubtangleMilestone": NULL_HASH,
+            "latestSolidSubtangleMilestoneIndex": if synced {
+                LATEST_MILESTONE_INDEX
+            } else {
+                LATEST_MILESTONE_INDEX - UNSYNCED_LAG
+            },
+            "neighbors": 0,
+            "time": now(),
+            "tips": tangle.trytes.len(),
+            "transactionsToRequest": 0,
+            "features": ["RemotePOW"],
+            "duration": 0,
+        })),
+        "getTransactionsToApprove" => {
+            let tip = tangle
+                .latest
+                .clone()
+                .unwrap_or_else(|| NULL_HASH.to_string());
+            Ok(json!({
+                "trunkTransaction": tip,
+                "branchTransaction": tip,
+                "duration": 0,
+            }))
+        }
+        "attachToTangle" => {
+            let trunk = string_param(request, "trunkTransaction")?;
+            let branch = string_param(request, "branchTransaction")?;
+            let trytes = list_param(request, "trytes");
+            Ok(json!({ "trytes": attach(&trunk, &branch, &trytes)?, "duration": 0 }))
+        }
+        "storeTransactions" | "broadcastTransactions" => {
+            for trytes in list_param(request, "trytes").iter() {
+                tangle.store(trytes)?;
+            }
+            Ok(json!({ "duration": 0 }))
+        }
+        "findTransactions" => {
+            let hashes = tangle.find(&[
+                (&tangle.addresses, list_param(request, "addresses"), 81),
+                (&tangle.tags, list_param(request, "tags"), 27),
+                (&tangle.bundles, list_param(request, "bundles"), 81),
+                (&tangle.approvees, list_param(request, "approvees"), 81),
+            ]);
+            Ok(json!({ "hashes": hashes, "duration": 0 }))
+        }
+        "getTrytes" => {
+            let trytes: Vec<String> = list_param(request, "hashes")
+                .iter()
+                .map(|hash| {
+                    tangle
+                        .trytes
+                        .get(hash)
+                        .cloned()
+                        .unwrap_or_else(|| "9".repeat(TRANSACTION_TRYTES))
+                })
+                .collect();
+            Ok(json!({ "trytes": trytes, "duration": 0 }))
+        }
+        "checkConsistency" => Ok(json!({ "state": true, "duration": 0 })),
+        _ => Err(format!("Command [{}] is unknown", command)),
+    }
+}
+
+///
+/// Chain the transactions of a bundle, the same way IRI does without the PoW
+///
+fn attach(trunk: &str, branch: &str, trytes: &[String]) -> Result<Vec<String>, String> {
+    let mut previous: Option<String> = None;
+    let mut attached = Vec::with_capacity(trytes.len());
+
+    for tx in trytes.iter() {
+        transaction_hash(tx)?;
+        let (trunk, branch) = match previous {
+            Some(ref hash) => (hash.as_str(), trunk),
+            None => (trunk, branch),
+        };
+
+        let timestamps = curl::trits_to_trytes(
+            &[
+                curl::int_to_trits(now(), 27),
+                curl::int_to_trits(0, 27),
+                curl::int_to_trits(MAX_TIMESTAMP_VALUE, 27),
+            ]
+            .concat(),
+        );
+        let tx = format!(
+            "{}{}{}{}{}{}",
+            &tx[..TRUNK.0],
+            trunk,
+            branch,
+            &tx[TAG.0..TAG.1],
+            timestamps,
+            &tx[NONCE.0..NONCE.1],
+        );
+
+        previous = Some(transaction_hash(&tx)?);
+        attached.push(tx);
+    }
+
+    attached.reverse();
+    Ok(attached)
+}
+
+fn transaction_hash(trytes: &str) -> Result<String, String> {
+    if trytes.len() != TRANSACTION_TRYTES {
+        return Err(format!("Invalid transaction length {}", trytes.len()));
+    }
+    curl::trytes_to_trits(trytes)
+        .map(|trits| curl::hash(&trits))
+        .ok_or_else(|| "Invalid trytes".to_string())
+}
+
+fn string_param(request: &Value, name: &str) -> Result<String, String> {
+    request[name]
+        .as_str()
+        .map(|value| value.to_string())
+        .ok_or_else(|| format!("Missing {}", name))
+}
+
+fn list_param(request: &Value, name: &str) -> Vec<String> {
+    request[name]
+        .as_array()
+        .map(|values| {
+            values
+                .iter()
+                .filter_map(|value| value.as_str().map(|value| value.to_string()))
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+fn pad(value: &str, length: usize) -> String {
+    let mut value = value.to_string();
+    value.truncate(length);
+    while value.len() < length {
+        value.push('9');
+    }
+    value
+}
+
+fn now() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap()
+        .as_millis() as i64
+}
@@ -1,6 +1,9 @@
 //!
 //! Utils Module
 //!
+pub mod chunked;
+#[cfg(feature = "mock-node")]
+pub mod mock_node;
 pub mod payload;
 pub mod random_seed;
 pub mod response_write_signed;
@@ -0,0 +1,38 @@
+//!
+//! Bincode Payload Serialization module
+//!
+use super::envelope::{ContentType, PayloadFormat};
+use super::{PayloadDeserializer, PayloadSerializer};
+use crate::channels_lite::Result;
+use serde::{de::DeserializeOwned, Serialize};
+
+///
+/// Implementation of Bincode Serialize
+///
+pub struct BincodeSerializer;
+
+impl PayloadFormat for BincodeSerializer {
+    fn content_type() -> ContentType {
+        ContentType::Bincode
+    }
+}
+
+impl<T: Serialize> PayloadSerializer<T> for BincodeSerializer {
+    fn serialize_data(data: &T) -> Result<Vec<u8>> {
+        ::bincode::serialize(data).map_err(super::malformed)
+    }
+}
+
+impl<T: DeserializeOwned> PayloadDeserializer<T> for BincodeSerializer {
+    fn deserialize_data(data: &[u8]) -> Result<T> {
+        ::bincode::deserialize(data).map_err(super::malformed)
+    }
+}
+
+/// Payload Bincode
+///
+pub type Payload = super::Payload<BincodeSerializer>;
+
+/// Payload Builder in Bincode Format
+///
+pub type PayloadBuilder = super::PayloadBuilder<BincodeSerializer>;
@@ -0,0 +1,38 @@
+//!
+//! CBOR Payload Serialization module
+//!
+use super::envelope::{ContentType, PayloadFormat};
+use super::{PayloadDeserializer, PayloadSerializer};
+use crate::channels_lite::Result;
+use serde::{de::DeserializeOwned, Serialize};
+
+///
+/// Implementation of CBOR Serialize
+///
+pub struct CborSerializer;
+
+impl PayloadFormat for CborSerializer {
+    fn content_type() -> ContentType {
+        ContentType::Cbor
+    }
+}
+
+impl<T: Serialize> PayloadSerializer<T> for CborSerializer {
+    fn serialize_data(data: &T) -> Result<Vec<u8>> {
+        serde_cbor::to_vec(data).map_err(super::malformed)
+    }
+}
+
+impl<T: DeserializeOwned> PayloadDeserializer<T> for CborSerializer {
+    fn deserialize_data(data: &[u8]) -> Result<T> {
+        serde_cbor::from_slice(data).map_err(super::malformed)
+    }
+}
+
+/// Payload CBOR
+///
+pub type Payload = super::Payload<CborSerializer>;
+
+/// Payload Builder in CBOR Format
+///
+pub type PayloadBuilder = super::PayloadBuilder<CborSerializer>;
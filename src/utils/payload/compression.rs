@@ -0,0 +1,108 @@
+//!
+//! Payload Compression
+//!
+//! The algorithms are behind the `deflate` and `zstd` features. Payloads are
+//! always decompressed through a bounded reader, so that a small message can
+//! not expand into an unbounded amount of memory.
+//!
+use crate::channels_lite::{ChannelError, Result};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+///
+/// Largest data accepted once decompressed, in bytes
+///
+pub const MAX_DECOMPRESSED_SIZE: usize = 1024 * 1024;
+
+///
+/// Compression algorithm of the data, recorded in the envelope
+///
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Compression {
+    /// Deflate (RFC 1951), requires the `deflate` feature
+    ///
+    Deflate,
+    /// Zstandard, requires the `zstd` feature
+    ///
+    Zstd,
+}
+
+impl fmt::Display for Compression {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Deflate => write!(f, "deflate"),
+            Self::Zstd => write!(f, "zstd"),
+        }
+    }
+}
+
+impl Compression {
+    ///
+    /// Compress the data
+    ///
+    #[cfg_attr(
+        not(any(feature = "deflate", feature = "zstd")),
+        allow(unused_variables)
+    )]
+    pub fn compress(self, data: &[u8]) -> Result<Vec<u8>> {
+        match self {
+            #[cfg(feature = "deflate")]
+            Self::Deflate => {
+                use std::io::Write;
+                let mut encoder =
+                    flate2::write::DeflateEncoder::new(Vec::new(), flate2::Compression::default());
+                encoder
+                    .write_all(data)
+                    .and_then(|()| encoder.finish())
+                    .map_err(super::malformed)
+            }
+            #[cfg(feature = "zstd")]
+            Self::Zstd => zstd::stream::encode_all(data, 0).map_err(super::malformed),
+            #[allow(unreachable_patterns)]
+            compression => Err(not_enabled(compression)),
+        }
+    }
+
+    ///
+    /// Decompress the data, failing once more than `max_size` bytes come out
+    ///
+    #[cfg_attr(
+        not(any(feature = "deflate", feature = "zstd")),
+        allow(unused_variables)
+    )]
+    pub fn decompress(self, data: &[u8], max_size: usize) -> Result<Vec<u8>> {
+        match self {
+            #[cfg(feature = "deflate")]
+            Self::Deflate => read_bounded(flate2::read::DeflateDecoder::new(data), max_size),
+            #[cfg(feature = "zstd")]
+            Self::Zstd => zstd::stream::read::Decoder::new(data)
+                .map_err(super::malformed)
+                .and_then(|decoder| read_bounded(decoder, max_size)),
+            #[allow(unreachable_patterns)]
+            compression => Err(not_enabled(compression)),
+        }
+    }
+}
+
+#[cfg(any(feature = "deflate", feature = "zstd"))]
+fn read_bounded<R: std::io::Read>(reader: R, max_size: usize) -> Result<Vec<u8>> {
+    use std::io::Read;
+    let mut data = Vec::new();
+    reader
+        .take(max_size as u64 + 1)
+        .read_to_end(&mut data)
+        .map_err(super::malformed)?;
+    if data.len() > max_size {
+        return Err(ChannelError::MalformedPayload(format!(
+            "Payload decompresses to more than {} bytes",
+            max_size
+        )));
+    }
+    Ok(data)
+}
+
+#[allow(dead_code)]
+fn not_enabled(compression: Compression) -> ChannelError {
+    ChannelError::MalformedPayload(format!("The {} feature is not enabled", compression))
+}
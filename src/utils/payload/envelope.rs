@@ -0,0 +1,295 @@
+//!
+//! Payload Envelope
+//!
+//! Self-describing wrapper of the public and masked data: the header tells
+//! the subscriber which serializer and which application schema produced
+//! the data, and carries a checksum of it.
+//!
+//! Layout: `ENVELOPE_MARKER`, header length (2 bytes, big endian), header
+//! in JSON, then the serialized data, compressed when the header says so.
+//!
+use super::compression::{Compression, MAX_DECOMPRESSED_SIZE};
+use super::{malformed, PayloadDeserializer};
+use crate::channels_lite::{ChannelError, Result};
+use base64::{encode_config, URL_SAFE_NO_PAD};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::fmt;
+
+///
+/// First byte of an enveloped payload, never found in the tryte encoding
+///
+pub(crate) const ENVELOPE_MARKER: u8 = 0x02;
+
+///
+/// Version of the envelope layout written by this release
+///
+pub const ENVELOPE_VERSION: u8 = 1;
+
+///
+/// Serialization format of the data
+///
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ContentType {
+    /// JSON
+    ///
+    Json,
+    /// CBOR
+    ///
+    Cbor,
+    /// MessagePack
+    ///
+    MsgPack,
+    /// Bincode
+    ///
+    Bincode,
+    /// Protocol Buffers
+    ///
+    Protobuf,
+    /// Any other format, named by the application
+    ///
+    Other(String),
+}
+
+impl fmt::Display for ContentType {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Json => write!(f, "json"),
+            Self::Cbor => write!(f, "cbor"),
+            Self::MsgPack => write!(f, "msgpack"),
+            Self::Bincode => write!(f, "bincode"),
+            Self::Protobuf => write!(f, "protobuf"),
+            Self::Other(name) => write!(f, "{}", name),
+        }
+    }
+}
+
+///
+/// Format written by a serializer, recorded in the envelope
+///
+pub trait PayloadFormat {
+    ///
+    /// Content type of the serialized data
+    ///
+    fn content_type() -> ContentType;
+}
+
+///
+/// Application schema of the data
+///
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Schema {
+    /// Schema name
+    ///
+    pub name: String,
+    /// Schema version
+    ///
+    pub version: u32,
+}
+
+impl Schema {
+    ///
+    /// Create Instance
+    ///
+    pub fn new<N: Into<String>>(name: N, version: u32) -> Self {
+        Self {
+            name: name.into(),
+            version: version,
+        }
+    }
+}
+
+///
+/// Envelope header
+///
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct EnvelopeHeader {
+    /// Version of the envelope layout
+    ///
+    pub version: u8,
+    /// Serialization format of the data
+    ///
+    pub content_type: ContentType,
+    /// Application schema of the data
+    ///
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub schema: Option<Schema>,
+    /// Creation timestamp in milliseconds
+    ///
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub created: Option<i64>,
+    /// Compression of the data in the message
+    ///
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub compression: Option<Compression>,
+    /// SHA-256 of the data, before compression, base64 encoded
+    ///
+    pub checksum: String,
+}
+
+///
+/// Enveloped data, checked against its header
+///
+/// The data is kept uncompressed, it is compressed when encoded.
+///
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Envelope {
+    /// Envelope header
+    ///
+    pub header: EnvelopeHeader,
+    /// Serialized data
+    ///
+    pub data: Vec<u8>,
+}
+
+impl Envelope {
+    ///
+    /// Wrap the serialized data
+    ///
+    pub fn new(
+        content_type: ContentType,
+        schema: Option<Schema>,
+        created: Option<i64>,
+        data: Vec<u8>,
+    ) -> Self {
+        Self {
+            header: EnvelopeHeader {
+                version: ENVELOPE_VERSION,
+                content_type: content_type,
+                schema: schema,
+                created: created,
+                compression: None,
+                checksum: checksum(&data),
+            },
+            data: data,
+        }
+    }
+
+    ///
+    /// Compress the data with the given algorithm once encoded
+    ///
+    pub fn compressed(mut self, compression: Option<Compression>) -> Self {
+        self.header.compression = compression;
+        self
+    }
+
+    ///
+    /// Deserialize the data with the serializer matching its content type
+    ///
+    /// JSON is always available, the other serde formats when their feature
+    /// is enabled. Use `decode_with()` for the protobuf and custom formats.
+    ///
+    pub fn decode<T: DeserializeOwned>(&self) -> Result<T> {
+        match self.header.content_type {
+            ContentType::Json => super::json::JsonSerializer::deserialize_data(&self.data),
+            #[cfg(feature = "cbor")]
+            ContentType::Cbor => super::cbor::CborSerializer::deserialize_data(&self.data),
+            #[cfg(feature = "msgpack")]
+            ContentType::MsgPack => super::msgpack::MsgPackSerializer::deserialize_data(&self.data),
+            #[cfg(feature = "bincode")]
+            ContentType::Bincode => super::bincode::BincodeSerializer::deserialize_data(&self.data),
+            ref content_type => Err(ChannelError::MalformedPayload(format!(
+                "No serializer enabled for the {} content type",
+                content_type
+            ))),
+        }
+    }
+
+    ///
+    /// Deserialize the data with the given serializer, which must match
+    /// the content type
+    ///
+    pub fn decode_with<S, T>(&self) -> Result<T>
+    where
+        S: PayloadDeserializer<T> + PayloadFormat,
+    {
+        check_content_type::<S>(&self.header)?;
+        S::deserialize_data(&self.data)
+    }
+
+    ///
+    /// Encode the envelope, marker included
+    ///
+    pub(crate) fn to_bytes(&self) -> Result<Vec<u8>> {
+        let header = serde_json::to_vec(&self.header).map_err(malformed)?;
+        if header.len() > u16::MAX as usize {
+            return Err(ChannelError::MalformedPayload(
+                "Envelope header too large".to_string(),
+            ));
+        }
+        let data = match self.header.compression {
+            Some(compression) => compression.compress(&self.data)?,
+            None => self.data.clone(),
+        };
+
+        let mut bytes = Vec::with_capacity(3 + header.len() + data.len());
+        bytes.push(ENVELOPE_MARKER);
+        bytes.extend_from_slice(&(header.len() as u16).to_be_bytes());
+        bytes.extend_from_slice(&header);
+        bytes.extend_from_slice(&data);
+        Ok(bytes)
+    }
+
+    ///
+    /// Decode and validate an envelope, marker included
+    ///
+    /// Compressed data is rejected once it grows past `MAX_DECOMPRESSED_SIZE`.
+    ///
+    pub(crate) fn from_bytes(bytes: &[u8]) -> Result<Self> {
+        if bytes.len() < 3 || bytes[0] != ENVELOPE_MARKER {
+            return Err(ChannelError::MalformedPayload(
+                "Payload is not enveloped".to_string(),
+            ));
+        }
+        let header_len = u16::from_be_bytes([bytes[1], bytes[2]]) as usize;
+        if bytes.len() < 3 + header_len {
+            return Err(ChannelError::MalformedPayload(
+                "Truncated envelope header".to_string(),
+            ));
+        }
+
+        let header: EnvelopeHeader =
+            serde_json::from_slice(&bytes[3..3 + header_len]).map_err(malformed)?;
+        if header.version != ENVELOPE_VERSION {
+            return Err(ChannelError::MalformedPayload(format!(
+                "Unsupported envelope version {}",
+                header.version
+            )));
+        }
+        let data = match header.compression {
+            Some(compression) => {
+                compression.decompress(&bytes[3 + header_len..], MAX_DECOMPRESSED_SIZE)?
+            }
+            None => bytes[3 + header_len..].to_vec(),
+        };
+        if checksum(&data) != header.checksum {
+            return Err(ChannelError::MalformedPayload(
+                "Envelope checksum mismatch".to_string(),
+            ));
+        }
+
+        Ok(Self {
+            header: header,
+            data: data,
+        })
+    }
+}
+
+///
+/// Reject the data written by another serializer than `S`
+///
+pub(crate) fn check_content_type<S: PayloadFormat>(header: &EnvelopeHeader) -> Result<()> {
+    let expected = S::content_type();
+    if header.content_type != expected {
+        return Err(ChannelError::MalformedPayload(format!(
+            "Payload is {}, not {}",
+            header.content_type, expected
+        )));
+    }
+    Ok(())
+}
+
+fn checksum(data: &[u8]) -> String {
+    encode_config(&Sha256::digest(data), URL_SAFE_NO_PAD)
+}
@@ -0,0 +1,38 @@
+//!
+//! JSON Payload Serialization module
+//!
+use super::envelope::{ContentType, PayloadFormat};
+use super::{PayloadDeserializer, PayloadSerializer};
+use crate::channels_lite::Result;
+use serde::{de::DeserializeOwned, Serialize};
+
+///
+/// Implementation of JSON Serialize
+///
+pub struct JsonSerializer;
+
+impl PayloadFormat for JsonSerializer {
+    fn content_type() -> ContentType {
+        ContentType::Json
+    }
+}
+
+impl<T: Serialize> PayloadSerializer<T> for JsonSerializer {
+    fn serialize_data(data: &T) -> Result<Vec<u8>> {
+        serde_json::to_vec(data).map_err(super::malformed)
+    }
+}
+
+impl<T: DeserializeOwned> PayloadDeserializer<T> for JsonSerializer {
+    fn deserialize_data(data: &[u8]) -> Result<T> {
+        serde_json::from_slice(data).map_err(super::malformed)
+    }
+}
+
+/// Payload JSON
+///
+pub type Payload = super::Payload<JsonSerializer>;
+
+/// Payload Builder in JSON Format
+///
+pub type PayloadBuilder = super::PayloadBuilder<JsonSerializer>;
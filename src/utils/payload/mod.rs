@@ -0,0 +1,340 @@
+//!
+//! Payload Module
+//!
+#[cfg(feature = "bincode")]
+pub mod bincode;
+#[cfg(feature = "cbor")]
+pub mod cbor;
+pub mod compression;
+pub mod envelope;
+pub mod json;
+#[cfg(feature = "msgpack")]
+pub mod msgpack;
+#[cfg(feature = "protobuf")]
+pub mod protobuf;
+
+use crate::channels_lite::{ChannelError, Result};
+use base64::{decode_config, encode_config, URL_SAFE_NO_PAD};
+use chrono::Utc;
+use compression::Compression;
+use envelope::{Envelope, PayloadFormat, Schema, ENVELOPE_MARKER};
+use iota_conversion::trytes_converter::{to_string as trytes_to_string, to_trytes};
+use iota_streams::app::transport::tangle::PAYLOAD_BYTES;
+use iota_streams::ddml::types::Bytes;
+use std::marker::PhantomData;
+
+///
+/// Maximum encoded size of the public and masked data of a message
+///
+pub const MAX_PAYLOAD_SIZE: usize = PAYLOAD_BYTES;
+
+///
+/// First byte of a binary payload, never found in the tryte encoding
+///
+const BINARY_MARKER: u8 = 0x01;
+
+///
+/// Encoding of the payload data in the message
+///
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PayloadEncoding {
+    /// Serialized data, base64 encoded and converted to trytes, as in the
+    /// first releases
+    ///
+    Trytes,
+    /// Serialized data as raw bytes, after a format marker
+    ///
+    Binary,
+    /// Serialized data as raw bytes, in an envelope telling its content
+    /// type and schema
+    ///
+    Envelope,
+}
+
+impl Default for PayloadEncoding {
+    fn default() -> Self {
+        Self::Trytes
+    }
+}
+
+///
+/// Simple Trait to transform the payload data to bytes
+///
+/// Implemented for every serde type by the serde based serializers, and for
+/// the `prost::Message` types by the protobuf one.
+///
+pub trait PayloadSerializer<T> {
+    ///
+    /// Transform data to bytes
+    ///
+    fn serialize_data(data: &T) -> Result<Vec<u8>>;
+}
+
+///
+/// Simple Trait to transform bytes back to the payload data
+///
+pub trait PayloadDeserializer<T> {
+    ///
+    /// Transform bytes to data
+    ///
+    fn deserialize_data(data: &[u8]) -> Result<T>;
+}
+
+///
+/// Payload
+///
+pub trait PacketPayload {
+    ///
+    /// Return the public payload data
+    ///
+    fn public_data(&self) -> &Bytes;
+    ///
+    /// Return the masked payload data
+    ///
+    fn masked_data(&self) -> &Bytes;
+    ///
+    /// Encoded size of the public and masked data, in bytes
+    ///
+    fn encoded_size(&self) -> usize {
+        self.public_data().0.len() + self.masked_data().0.len()
+    }
+}
+
+///
+/// Reject the payloads that do not fit in a message
+///
+pub fn check_size<P: PacketPayload>(payload: &P) -> Result<()> {
+    check_encoded_size(payload.encoded_size())
+}
+
+///
+/// Packet
+///
+pub struct Payload<S> {
+    public: Bytes,
+    masked: Bytes,
+    _marker: PhantomData<S>,
+}
+
+impl<S> Payload<S> {
+    ///
+    /// Unwrap the data as text, for text serializers such as JSON
+    ///
+    pub fn unwrap_data(data: &[u8]) -> Result<Option<String>> {
+        match Self::decode(data)? {
+            Some(decode_data) => Ok(Some(String::from_utf8(decode_data).map_err(malformed)?)),
+            None => Ok(None),
+        }
+    }
+
+    ///
+    /// Unwrap and deserialize the data
+    ///
+    /// Enveloped data must have been written with the same serializer.
+    ///
+    pub fn unwrap_data_as<T>(data: &[u8]) -> Result<Option<T>>
+    where
+        S: PayloadDeserializer<T> + PayloadFormat,
+    {
+        if data.first() == Some(&ENVELOPE_MARKER) {
+            return Envelope::from_bytes(data)?.decode_with::<S, T>().map(Some);
+        }
+        match Self::decode(data)? {
+            Some(decode_data) => Ok(Some(S::deserialize_data(&decode_data)?)),
+            None => Ok(None),
+        }
+    }
+
+    ///
+    /// Unwrap the envelope of the data, checking its version and checksum
+    ///
+    /// Fails with `MalformedPayload` when the data is not enveloped.
+    ///
+    pub fn unwrap_envelope(data: &[u8]) -> Result<Option<Envelope>> {
+        if data.is_empty() {
+            return Ok(None);
+        }
+        Envelope::from_bytes(data).map(Some)
+    }
+
+    fn decode(data: &[u8]) -> Result<Option<Vec<u8>>> {
+        if data.is_empty() {
+            return Ok(None);
+        }
+        if data[0] == BINARY_MARKER {
+            return Ok(Some(data[1..].to_vec()));
+        }
+        if data[0] == ENVELOPE_MARKER {
+            return Ok(Some(Envelope::from_bytes(data)?.data));
+        }
+        let data_str = String::from_utf8(data.to_vec()).map_err(malformed)?;
+        let raw = trytes_to_string(&data_str).map_err(malformed)?;
+        let decode_data = decode_config(&raw, URL_SAFE_NO_PAD).map_err(malformed)?;
+        Ok(Some(decode_data))
+    }
+}
+
+impl<S> PacketPayload for Payload<S> {
+    fn public_data(&self) -> &Bytes {
+        &self.public
+    }
+    fn masked_data(&self) -> &Bytes {
+        &self.masked
+    }
+}
+
+///
+/// Payload Builder
+///
+pub struct PayloadBuilder<S> {
+    p_data: Vec<u8>,
+    m_data: Vec<u8>,
+    encoding: PayloadEncoding,
+    schema: Option<Schema>,
+    compression: Option<Compression>,
+    _marker: PhantomData<S>,
+}
+
+impl<S> PayloadBuilder<S> {
+    ///
+    /// Create Instance
+    ///
+    pub fn new() -> Self {
+        Self::with_encoding(PayloadEncoding::default())
+    }
+
+    ///
+    /// Create Instance using the given encoding
+    ///
+    /// Subscribers read both encodings, `Binary` is about four times more
+    /// compact but can not be read by the releases before it.
+    ///
+    pub fn with_encoding(encoding: PayloadEncoding) -> Self {
+        PayloadBuilder {
+            p_data: Vec::new(),
+            m_data: Vec::new(),
+            encoding: encoding,
+            schema: None,
+            compression: None,
+            _marker: PhantomData,
+        }
+    }
+
+    ///
+    /// Create Instance writing the data in an envelope, tagged with the
+    /// application schema
+    ///
+    pub fn with_schema(schema: Schema) -> Self {
+        PayloadBuilder {
+            schema: Some(schema),
+            ..Self::with_encoding(PayloadEncoding::Envelope)
+        }
+    }
+
+    ///
+    /// Compress the public and masked data
+    ///
+    /// The compression is recorded in an envelope, which replaces the
+    /// encoding of the builder, so the subscribers decompress the data
+    /// without being told. Fails with `MalformedPayload` when the data is
+    /// already set, it would stay uncompressed in the previous encoding.
+    ///
+    pub fn compressed(mut self, compression: Compression) -> Result<Self> {
+        if !self.p_data.is_empty() || !self.m_data.is_empty() {
+            return Err(ChannelError::MalformedPayload(
+                "Compression must be set before the data".to_string(),
+            ));
+        }
+        self.encoding = PayloadEncoding::Envelope;
+        self.compression = Some(compression);
+        Ok(self)
+    }
+
+    ///
+    /// Public Data
+    ///
+    /// Fails with `PayloadTooLarge` when the payload would not fit in a message
+    ///
+    pub fn public<T>(&mut self, data: &T) -> Result<&mut Self>
+    where
+        S: PayloadSerializer<T> + PayloadFormat,
+    {
+        let p_data = self.encode(data)?;
+        check_encoded_size(p_data.len() + self.m_data.len())?;
+        self.p_data = p_data;
+        Ok(self)
+    }
+
+    ///
+    /// Masked Data
+    ///
+    /// Fails with `PayloadTooLarge` when the payload would not fit in a message
+    ///
+    pub fn masked<T>(&mut self, data: &T) -> Result<&mut Self>
+    where
+        S: PayloadSerializer<T> + PayloadFormat,
+    {
+        let m_data = self.encode(data)?;
+        check_encoded_size(self.p_data.len() + m_data.len())?;
+        self.m_data = m_data;
+        Ok(self)
+    }
+
+    ///
+    /// Encoded size of the public and masked data, in bytes
+    ///
+    pub fn encoded_size(&self) -> usize {
+        self.p_data.len() + self.m_data.len()
+    }
+
+    ///
+    /// Build
+    ///
+    pub fn build(&self) -> Payload<S> {
+        Payload {
+            public: Bytes(self.p_data.clone()),
+            masked: Bytes(self.m_data.clone()),
+            _marker: PhantomData,
+        }
+    }
+
+    fn encode<T>(&self, data: &T) -> Result<Vec<u8>>
+    where
+        S: PayloadSerializer<T> + PayloadFormat,
+    {
+        let serialized = S::serialize_data(data)?;
+        match self.encoding {
+            PayloadEncoding::Trytes => to_trytes(&encode_config(&serialized, URL_SAFE_NO_PAD))
+                .map(String::into_bytes)
+                .map_err(malformed),
+            PayloadEncoding::Binary => {
+                let mut bytes = Vec::with_capacity(serialized.len() + 1);
+                bytes.push(BINARY_MARKER);
+                bytes.extend_from_slice(&serialized);
+                Ok(bytes)
+            }
+            PayloadEncoding::Envelope => Envelope::new(
+                S::content_type(),
+                self.schema.clone(),
+                Some(Utc::now().timestamp_millis()),
+                serialized,
+            )
+            .compressed(self.compression)
+            .to_bytes(),
+        }
+    }
+}
+
+fn check_encoded_size(size: usize) -> Result<()> {
+    if size > MAX_PAYLOAD_SIZE {
+        return Err(ChannelError::PayloadTooLarge {
+            size: size,
+            max: MAX_PAYLOAD_SIZE,
+        });
+    }
+    Ok(())
+}
+
+fn malformed<E: std::fmt::Display>(error: E) -> ChannelError {
+    ChannelError::MalformedPayload(error.to_string())
+}
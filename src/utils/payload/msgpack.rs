@@ -0,0 +1,38 @@
+//!
+//! MessagePack Payload Serialization module
+//!
+use super::envelope::{ContentType, PayloadFormat};
+use super::{PayloadDeserializer, PayloadSerializer};
+use crate::channels_lite::Result;
+use serde::{de::DeserializeOwned, Serialize};
+
+///
+/// Implementation of MessagePack Serialize
+///
+pub struct MsgPackSerializer;
+
+impl PayloadFormat for MsgPackSerializer {
+    fn content_type() -> ContentType {
+        ContentType::MsgPack
+    }
+}
+
+impl<T: Serialize> PayloadSerializer<T> for MsgPackSerializer {
+    fn serialize_data(data: &T) -> Result<Vec<u8>> {
+        rmp_serde::to_vec_named(data).map_err(super::malformed)
+    }
+}
+
+impl<T: DeserializeOwned> PayloadDeserializer<T> for MsgPackSerializer {
+    fn deserialize_data(data: &[u8]) -> Result<T> {
+        rmp_serde::from_read_ref(data).map_err(super::malformed)
+    }
+}
+
+/// Payload MessagePack
+///
+pub type Payload = super::Payload<MsgPackSerializer>;
+
+/// Payload Builder in MessagePack Format
+///
+pub type PayloadBuilder = super::PayloadBuilder<MsgPackSerializer>;
@@ -0,0 +1,44 @@
+//!
+//! Protobuf Payload Serialization module
+//!
+//! Works with the types generated by `prost`, or derived with
+//! `#[derive(prost::Message)]`, so the data can be read by any protobuf
+//! implementation sharing the same `.proto` schema.
+//!
+use super::envelope::{ContentType, PayloadFormat};
+use super::{PayloadDeserializer, PayloadSerializer};
+use crate::channels_lite::Result;
+use prost::Message;
+
+///
+/// Implementation of Protobuf Serialize
+///
+pub struct ProtobufSerializer;
+
+impl PayloadFormat for ProtobufSerializer {
+    fn content_type() -> ContentType {
+        ContentType::Protobuf
+    }
+}
+
+impl<T: Message> PayloadSerializer<T> for ProtobufSerializer {
+    fn serialize_data(data: &T) -> Result<Vec<u8>> {
+        let mut bytes = Vec::with_capacity(data.encoded_len());
+        data.encode(&mut bytes).map_err(super::malformed)?;
+        Ok(bytes)
+    }
+}
+
+impl<T: Message + Default> PayloadDeserializer<T> for ProtobufSerializer {
+    fn deserialize_data(data: &[u8]) -> Result<T> {
+        T::decode(data).map_err(super::malformed)
+    }
+}
+
+/// Payload Protobuf
+///
+pub type Payload = super::Payload<ProtobufSerializer>;
+
+/// Payload Builder in Protobuf Format
+///
+pub type PayloadBuilder = super::PayloadBuilder<ProtobufSerializer>;
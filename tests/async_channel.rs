@@ -0,0 +1,40 @@
+mod common;
+
+use channels_lite::channels::channel_author::{self, Channel as Author};
+use channels_lite::channels::channel_subscriber::{self, Channel as Subscriber};
+use channels_lite::channels::transport::InMemoryTransport;
+use channels_lite::utils::payload::json::JsonSerializer;
+use common::{payload, SensorData};
+
+#[tokio::test]
+async fn async_channels_share_a_masked_message() {
+    let tangle = InMemoryTransport::new();
+    let author: channel_author::AsyncChannel<InMemoryTransport> =
+        Author::with_transport(tangle.clone(), None).unwrap().into();
+    let (address, announcement_tag) = author.open().await.unwrap();
+
+    let subscriber: channel_subscriber::AsyncChannel<InMemoryTransport> =
+        Subscriber::with_transport(tangle.clone(), address, announcement_tag, None)
+            .unwrap()
+            .into();
+    let subscription_tag = subscriber.connect().await.unwrap();
+    let keyload_tag = author.add_subscriber(subscription_tag).await.unwrap();
+    subscriber.update_keyload(keyload_tag).await.unwrap();
+
+    let signed_tag = author.write_signed(true, payload(1)).await.unwrap();
+    let mut messages = subscriber
+        .read_signed_as::<JsonSerializer, SensorData, SensorData>(signed_tag)
+        .await
+        .unwrap();
+    assert_eq!(messages.len(), 1);
+    assert_eq!(
+        messages.remove(0).unwrap().masked,
+        Some(SensorData::new(1, 2.0))
+    );
+
+    let subscribers = author
+        .with_channel(|channel| Ok(channel.subscribers().len()))
+        .await
+        .unwrap();
+    assert_eq!(subscribers, 1);
+}
@@ -0,0 +1,103 @@
+mod common;
+
+use channels_lite::channels::channel_author::Channel as Author;
+use channels_lite::channels::transport::InMemoryTransport;
+use channels_lite::channels::ChannelError;
+use common::{connect, payload, read_masked, FlakyTransport};
+
+#[test]
+fn author_state_is_replayed_on_import() {
+    let tangle = InMemoryTransport::new();
+    let mut author = Author::with_transport(tangle.clone(), None).unwrap();
+    let (address, announcement_tag) = author.open().unwrap();
+    let mut subscriber = connect(&tangle, &mut author, &address, &announcement_tag);
+    for ts in 0..5 {
+        author.write_signed(true, payload(ts)).unwrap();
+        author.write_tagged(payload(ts)).unwrap();
+    }
+    let subscribers = author.subscribers();
+
+    let snapshot = author.export_state("password").unwrap();
+    drop(author);
+    assert!(Author::import_state_with_transport(tangle.clone(), &snapshot, "wrong").is_err());
+
+    let mut author =
+        Author::import_state_with_transport(tangle.clone(), &snapshot, "password").unwrap();
+    assert_eq!(author.subscribers(), subscribers);
+    assert!(author.needs_keyload());
+    assert_eq!(author.keyload_tag(), None);
+
+    let keyloads = author.reshare_keyloads().unwrap();
+    assert!(!author.needs_keyload());
+    assert_eq!(author.keyload_tag(), Some(keyloads.keyload_tag.clone()));
+    subscriber.update_keyload(keyloads.keyload_tag).unwrap();
+
+    let signed_tag = author.write_signed(true, payload(42)).unwrap();
+    assert_eq!(read_masked(&mut subscriber, signed_tag).ts, 42);
+}
+
+#[test]
+fn import_does_not_touch_the_network() {
+    let tangle = InMemoryTransport::new();
+    let mut author = Author::with_transport(tangle.clone(), None).unwrap();
+    let (address, announcement_tag) = author.open().unwrap();
+    let mut subscriber = connect(&tangle, &mut author, &address, &announcement_tag);
+    let snapshot = author.export_state("password").unwrap();
+    drop(author);
+
+    let transport = FlakyTransport::new(&tangle, false);
+    let messages = tangle.len();
+    let mut author =
+        Author::import_state_with_transport(transport.clone(), &snapshot, "password").unwrap();
+    assert_eq!(tangle.len(), messages);
+    match author.write_signed(true, payload(1)) {
+        Err(ChannelError::NoKeyload) => {}
+        other => panic!("Expected NoKeyload, got {:?}", other),
+    }
+
+    transport.set_online(true);
+    let keyloads = author.reshare_keyloads().unwrap();
+    subscriber.update_keyload(keyloads.keyload_tag).unwrap();
+    let signed_tag = author.write_signed(true, payload(2)).unwrap();
+    assert_eq!(read_masked(&mut subscriber, signed_tag).ts, 2);
+}
+
+#[test]
+fn selective_keyloads_lost_on_import_are_returned() {
+    let tangle = InMemoryTransport::new();
+    let mut author = Author::with_transport(tangle.clone(), None).unwrap();
+    let (address, announcement_tag) = author.open().unwrap();
+    let mut crew = connect(&tangle, &mut author, &address, &announcement_tag);
+    let _customer = connect(&tangle, &mut author, &address, &announcement_tag);
+    let subscribers = author.subscribers();
+    let crew_tag = author.share_keyload_for(&subscribers[..1]).unwrap();
+
+    let snapshot = author.export_state("password").unwrap();
+    let mut author =
+        Author::import_state_with_transport(tangle.clone(), &snapshot, "password").unwrap();
+    assert_eq!(
+        author.lost_keyloads(),
+        vec![(crew_tag.clone(), subscribers[..1].to_vec())]
+    );
+    match author.write_signed_with_keyload(&crew_tag, payload(1)) {
+        Err(ChannelError::InvalidTag(_)) => {}
+        other => panic!("Expected InvalidTag, got {:?}", other),
+    }
+
+    let keyloads = author.reshare_keyloads().unwrap();
+    assert!(author.lost_keyloads().is_empty());
+    assert_eq!(keyloads.selective_keyloads.len(), 1);
+    let (old_tag, new_tag) = keyloads.selective_keyloads[0].clone();
+    assert_eq!(old_tag, crew_tag);
+    let new_tag = new_tag.unwrap();
+    assert_eq!(
+        author.keyload_subscribers(&new_tag),
+        Some(subscribers[..1].to_vec())
+    );
+
+    crew.update_keyload(new_tag.clone()).unwrap();
+    let signed_tag = author
+        .write_signed_with_keyload(&new_tag, payload(3))
+        .unwrap();
+    assert_eq!(read_masked(&mut crew, signed_tag).ts, 3);
+}
@@ -0,0 +1,141 @@
+//!
+//! Fixtures shared by the integration tests
+//!
+#![allow(dead_code)]
+
+use channels_lite::channels::channel_author::Channel as Author;
+use channels_lite::channels::channel_subscriber::Channel as Subscriber;
+use channels_lite::channels::transport::{ChannelTransport, InMemoryTransport};
+use channels_lite::channels::{ChannelError, Result};
+use channels_lite::utils::payload::json::{JsonSerializer, Payload, PayloadBuilder};
+use iota_streams::app_channels::api::tangle::{Address, Message};
+use serde::{Deserialize, Serialize};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+
+///
+/// Some example of sensor Data
+///
+#[derive(Serialize, Deserialize, Debug, PartialEq, Clone)]
+pub struct SensorData {
+    pub ts: u64,
+    pub presure: f32,
+}
+
+impl SensorData {
+    pub fn new(ts: u64, presure: f32) -> Self {
+        Self {
+            ts: ts,
+            presure: presure,
+        }
+    }
+}
+
+///
+/// JSON payload with the public and masked data of the given timestamp
+///
+pub fn payload(ts: u64) -> Payload {
+    PayloadBuilder::new()
+        .public(&SensorData::new(ts, 1.0))
+        .unwrap()
+        .masked(&SensorData::new(ts, 2.0))
+        .unwrap()
+        .build()
+}
+
+///
+/// Announced channel and a subscriber not connected yet
+///
+pub fn open_channel(
+    tangle: &InMemoryTransport,
+) -> (Author<InMemoryTransport>, Subscriber<InMemoryTransport>) {
+    let mut author = Author::with_transport(tangle.clone(), None).unwrap();
+    let (channel_address, announcement_tag) = author.open().unwrap();
+    let subscriber =
+        Subscriber::with_transport(tangle.clone(), channel_address, announcement_tag, None)
+            .unwrap();
+    (author, subscriber)
+}
+
+///
+/// New subscriber of the channel, added by the author with its keyload
+///
+pub fn connect(
+    tangle: &InMemoryTransport,
+    author: &mut Author<InMemoryTransport>,
+    address: &str,
+    announcement_tag: &str,
+) -> Subscriber<InMemoryTransport> {
+    let mut subscriber = Subscriber::with_transport(
+        tangle.clone(),
+        address.to_string(),
+        announcement_tag.to_string(),
+        None,
+    )
+    .unwrap();
+    let subscription_tag = subscriber.connect().unwrap();
+    let keyload_tag = author.add_subscriber(subscription_tag).unwrap();
+    subscriber.update_keyload(keyload_tag).unwrap();
+    subscriber
+}
+
+///
+/// Announced channel with a connected subscriber holding the keyload
+///
+pub fn connected_channel(
+    tangle: &InMemoryTransport,
+) -> (Author<InMemoryTransport>, Subscriber<InMemoryTransport>) {
+    let mut author = Author::with_transport(tangle.clone(), None).unwrap();
+    let (address, announcement_tag) = author.open().unwrap();
+    let subscriber = connect(tangle, &mut author, &address, &announcement_tag);
+    (author, subscriber)
+}
+
+///
+/// Masked data of the single signed packet at the tag
+///
+pub fn read_masked(subscriber: &mut Subscriber<InMemoryTransport>, tag: String) -> SensorData {
+    let mut messages = subscriber
+        .read_signed_as::<JsonSerializer, SensorData, SensorData>(tag)
+        .unwrap();
+    assert_eq!(messages.len(), 1);
+    messages.remove(0).unwrap().masked.unwrap()
+}
+
+///
+/// In-memory Tangle that can be switched offline
+///
+#[derive(Clone)]
+pub struct FlakyTransport {
+    pub tangle: InMemoryTransport,
+    pub online: Arc<AtomicBool>,
+}
+
+impl FlakyTransport {
+    pub fn new(tangle: &InMemoryTransport, online: bool) -> Self {
+        Self {
+            tangle: tangle.clone(),
+            online: Arc::new(AtomicBool::new(online)),
+        }
+    }
+
+    pub fn set_online(&self, online: bool) {
+        self.online.store(online, Ordering::SeqCst);
+    }
+}
+
+impl ChannelTransport for FlakyTransport {
+    fn send_message(&mut self, message: &Message) -> Result<()> {
+        if !self.online.load(Ordering::SeqCst) {
+            return Err(ChannelError::Transport("No connectivity".to_string()));
+        }
+        self.tangle.send_message(message)
+    }
+
+    fn recv_messages(&mut self, link: &Address) -> Result<Vec<Message>> {
+        if !self.online.load(Ordering::SeqCst) {
+            return Err(ChannelError::Transport("No connectivity".to_string()));
+        }
+        self.tangle.recv_messages(link)
+    }
+}
@@ -0,0 +1,117 @@
+use channels_lite::channels::transport::TangleTransport;
+use channels_lite::channels::{channel_author, channel_subscriber, NetworkConfig};
+use channels_lite::utils::mock_node::MockNode;
+use channels_lite::utils::payload::json::{JsonSerializer, PayloadBuilder};
+
+struct Nodes {
+    healthy: MockNode,
+    offline: MockNode,
+    unsynced: MockNode,
+    stopped: MockNode,
+}
+
+///
+/// Four local nodes sharing the same Tangle: one stopped, one offline, one
+/// out of sync, listed before the healthy one
+///
+fn start_nodes() -> (Nodes, NetworkConfig) {
+    let healthy = MockNode::start().unwrap();
+    let offline = healthy.peer().unwrap();
+    let unsynced = healthy.peer().unwrap();
+    let mut stopped = healthy.peer().unwrap();
+    offline.set_online(false);
+    unsynced.set_synced(false);
+    stopped.stop();
+
+    let network = NetworkConfig {
+        nodes: vec![stopped.url(), offline.url(), unsynced.url(), healthy.url()],
+        min_weight_magnitude: 9,
+        ..NetworkConfig::default()
+    };
+    let nodes = Nodes {
+        healthy: healthy,
+        offline: offline,
+        unsynced: unsynced,
+        stopped: stopped,
+    };
+    (nodes, network)
+}
+
+#[test]
+fn health_check_reports_every_node() {
+    let (nodes, network) = start_nodes();
+    let mut transport = TangleTransport::new(network).unwrap();
+
+    let health = transport.check_health();
+    assert_eq!(health.len(), 4);
+    let node = |url: String| health.iter().find(|node| node.url == url).unwrap();
+    assert!(!node(nodes.stopped.url()).reachable);
+    assert!(!node(nodes.offline.url()).reachable);
+    assert!(node(nodes.unsynced.url()).reachable);
+    assert!(!node(nodes.unsynced.url()).synced);
+    assert!(node(nodes.healthy.url()).reachable);
+    assert!(node(nodes.healthy.url()).synced);
+    assert!(node(nodes.healthy.url()).latency.is_some());
+}
+
+#[test]
+fn requests_go_to_the_healthy_node_then_fall_back() {
+    let (nodes, network) = start_nodes();
+
+    let mut transport = TangleTransport::new(network.clone()).unwrap();
+    transport.check_health();
+    let mut author = channel_author::Channel::with_transport(transport, None).unwrap();
+    let (channel_address, announcement_tag) = author.open().unwrap();
+    let reports = author.take_send_reports();
+    assert_eq!(reports.len(), 1);
+    assert_eq!(reports[0].msgid, announcement_tag);
+    assert_eq!(reports[0].node, nodes.healthy.url());
+
+    let mut transport = TangleTransport::new(network).unwrap();
+    transport.check_health();
+    let mut subscriber = channel_subscriber::Channel::with_transport(
+        transport,
+        channel_address,
+        announcement_tag,
+        None,
+    )
+    .unwrap();
+    let subscription_tag = subscriber.connect().unwrap();
+    let reports = subscriber.take_send_reports();
+    assert_eq!(reports.len(), 1);
+    assert_eq!(reports[0].msgid, subscription_tag);
+    assert_eq!(reports[0].node, nodes.healthy.url());
+
+    // The healthy node goes down, the unsynced one is the only one left
+    nodes.healthy.set_online(false);
+
+    let keyload_tag = author.add_subscriber(subscription_tag).unwrap();
+    let tagged_tag = author
+        .write_tagged(
+            PayloadBuilder::new()
+                .public(&"Still online")
+                .unwrap()
+                .build(),
+        )
+        .unwrap();
+    let reports = author.take_send_reports();
+    assert_eq!(
+        reports
+            .iter()
+            .map(|report| report.msgid.clone())
+            .collect::<Vec<String>>(),
+        vec![keyload_tag.clone(), tagged_tag.clone()]
+    );
+    for report in reports.iter() {
+        assert_eq!(report.node, nodes.unsynced.url());
+        assert!(report.pow_duration.is_some());
+    }
+
+    subscriber.update_keyload(keyload_tag).unwrap();
+    let message = subscriber
+        .read_tagged_as::<JsonSerializer, String, String>(tagged_tag)
+        .unwrap()
+        .remove(0)
+        .unwrap();
+    assert_eq!(message.public, Some("Still online".to_string()));
+}
@@ -0,0 +1,92 @@
+mod common;
+
+use channels_lite::channels::channel_author::Channel as Author;
+use channels_lite::channels::channel_subscriber::Channel as Subscriber;
+use channels_lite::channels::transport::InMemoryTransport;
+use channels_lite::channels::ChannelError;
+use common::{connect, open_channel, payload};
+
+fn fetch_tags(subscriber: &mut Subscriber<InMemoryTransport>) -> Vec<String> {
+    subscriber
+        .fetch_next_messages()
+        .unwrap()
+        .into_iter()
+        .map(|msg| msg.unwrap().msgid)
+        .collect()
+}
+
+#[test]
+fn fetch_next_messages_in_order() {
+    let tangle = InMemoryTransport::new();
+    let (mut author, mut subscriber) = open_channel(&tangle);
+    let subscription_tag = subscriber.connect().unwrap();
+    author.add_subscriber(subscription_tag).unwrap();
+
+    let first = author.write_signed(true, payload(1)).unwrap();
+    let second = author.write_signed(false, payload(2)).unwrap();
+    assert_eq!(fetch_tags(&mut subscriber), vec![first, second]);
+    assert!(fetch_tags(&mut subscriber).is_empty());
+
+    let third = author.write_tagged(payload(3)).unwrap();
+    assert_eq!(fetch_tags(&mut subscriber), vec![third]);
+}
+
+#[test]
+fn fetch_requires_a_connection() {
+    let tangle = InMemoryTransport::new();
+    let (_author, mut subscriber) = open_channel(&tangle);
+
+    match subscriber.fetch_next_messages() {
+        Err(ChannelError::NotConnected) => {}
+        other => panic!("Expected NotConnected, got {:?}", other),
+    }
+}
+
+#[test]
+fn keyload_for_others_is_skipped() {
+    let tangle = InMemoryTransport::new();
+    let mut author = Author::with_transport(tangle.clone(), None).unwrap();
+    let (address, announcement_tag) = author.open().unwrap();
+    let _crew = connect(&tangle, &mut author, &address, &announcement_tag);
+    let mut customer = connect(&tangle, &mut author, &address, &announcement_tag);
+    let crew_id = author.subscribers().remove(0);
+
+    let crew_tag = author.share_keyload_for(&[crew_id]).unwrap();
+    let signed_tag = author.write_signed(false, payload(1)).unwrap();
+
+    let messages = customer.fetch_next_messages().unwrap();
+    let skipped: Vec<String> = messages
+        .iter()
+        .filter_map(|msg| match msg {
+            Err(ChannelError::SkippedMessage { msgid, .. }) => Some(msgid.clone()),
+            _ => None,
+        })
+        .collect();
+    assert!(skipped.contains(&crew_tag));
+    assert_eq!(
+        messages
+            .iter()
+            .filter_map(|msg| msg.as_ref().ok())
+            .map(|msg| msg.msgid.clone())
+            .last(),
+        Some(signed_tag)
+    );
+}
+
+#[test]
+fn restored_subscriber_resumes_after_the_last_fetched_message() {
+    let tangle = InMemoryTransport::new();
+    let (mut author, mut subscriber) = open_channel(&tangle);
+    let subscription_tag = subscriber.connect().unwrap();
+    author.add_subscriber(subscription_tag).unwrap();
+
+    let first = author.write_signed(true, payload(1)).unwrap();
+    assert_eq!(fetch_tags(&mut subscriber), vec![first]);
+    let snapshot = subscriber.export_state("password").unwrap();
+
+    let second = author.write_tagged(payload(2)).unwrap();
+    let mut restored =
+        Subscriber::import_state_with_transport(tangle.clone(), &snapshot, "password").unwrap();
+    assert_eq!(fetch_tags(&mut restored), vec![second]);
+    assert!(fetch_tags(&mut restored).is_empty());
+}
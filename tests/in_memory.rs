@@ -0,0 +1,61 @@
+mod common;
+
+use channels_lite::channels::transport::InMemoryTransport;
+use channels_lite::channels::ChannelError;
+use channels_lite::utils::payload::json::JsonSerializer;
+use common::{open_channel, payload, SensorData};
+
+#[test]
+fn signed_and_tagged_flow() {
+    let tangle = InMemoryTransport::new();
+    let (mut author, mut subscriber) = open_channel(&tangle);
+    assert_eq!(tangle.len(), 1);
+
+    let subscription_tag = subscriber.connect().unwrap();
+    let keyload_tag = author.add_subscriber(subscription_tag).unwrap();
+    assert_eq!(author.keyload_tag(), Some(keyload_tag.clone()));
+    assert_eq!(author.subscribers().len(), 1);
+    subscriber.update_keyload(keyload_tag).unwrap();
+
+    let signed_tag = author.write_signed(true, payload(1)).unwrap();
+    let tagged_tag = author.write_tagged(payload(2)).unwrap();
+    assert_eq!(tangle.len(), 5);
+
+    let signed = subscriber
+        .read_signed_as::<JsonSerializer, SensorData, SensorData>(signed_tag)
+        .unwrap()
+        .remove(0)
+        .unwrap();
+    assert_eq!(signed.public, Some(SensorData::new(1, 1.0)));
+    assert_eq!(signed.masked, Some(SensorData::new(1, 2.0)));
+
+    let tagged = subscriber
+        .read_tagged_as::<JsonSerializer, SensorData, SensorData>(tagged_tag)
+        .unwrap()
+        .remove(0)
+        .unwrap();
+    assert_eq!(tagged.public, Some(SensorData::new(2, 1.0)));
+    assert_eq!(tagged.masked, Some(SensorData::new(2, 2.0)));
+}
+
+#[test]
+fn clones_share_the_messages() {
+    let tangle = InMemoryTransport::new();
+    assert!(tangle.is_empty());
+    let (mut author, mut subscriber) = open_channel(&tangle.clone());
+
+    let subscription_tag = subscriber.connect().unwrap();
+    author.add_subscriber(subscription_tag).unwrap();
+    assert_eq!(tangle.len(), 3);
+}
+
+#[test]
+fn masked_message_requires_keyload() {
+    let tangle = InMemoryTransport::new();
+    let (mut author, _subscriber) = open_channel(&tangle);
+
+    match author.write_signed(true, payload(1)) {
+        Err(ChannelError::NoKeyload) => {}
+        other => panic!("Expected NoKeyload, got {:?}", other),
+    }
+}
@@ -0,0 +1,273 @@
+mod common;
+
+use channels_lite::channels::channel_author::Channel as Author;
+use channels_lite::channels::channel_subscriber::Channel as Subscriber;
+use channels_lite::channels::transport::{ChannelTransport, InMemoryTransport};
+use channels_lite::channels::{ChannelError, Result, RetryPolicy};
+use channels_lite::utils::payload::compression::Compression;
+use channels_lite::utils::payload::envelope::{ContentType, Schema};
+use channels_lite::utils::payload::json::{JsonSerializer, PayloadBuilder};
+use channels_lite::utils::payload::{PayloadEncoding, MAX_PAYLOAD_SIZE};
+use common::{connected_channel, SensorData};
+use iota_streams::app_channels::api::tangle::{Address, Message};
+
+///
+/// In-memory Tangle accepting a limited number of messages
+///
+struct LimitedTransport {
+    tangle: InMemoryTransport,
+    sends_left: usize,
+}
+
+impl ChannelTransport for LimitedTransport {
+    fn send_message(&mut self, message: &Message) -> Result<()> {
+        if self.sends_left == 0 {
+            return Err(ChannelError::Transport("No connectivity".to_string()));
+        }
+        self.sends_left -= 1;
+        self.tangle.send_message(message)
+    }
+
+    fn recv_messages(&mut self, link: &Address) -> Result<Vec<Message>> {
+        self.tangle.recv_messages(link)
+    }
+}
+
+fn blob(size: usize) -> Vec<u8> {
+    (0..size).map(|i| (i % 251) as u8).collect()
+}
+
+#[test]
+fn oversized_payload_is_rejected() {
+    let blob = vec![0u8; MAX_PAYLOAD_SIZE];
+    match PayloadBuilder::new().public(&blob) {
+        Err(ChannelError::PayloadTooLarge { size, max }) => {
+            assert!(size > max);
+            assert_eq!(max, MAX_PAYLOAD_SIZE);
+        }
+        Err(e) => panic!("Expected PayloadTooLarge, got {}", e),
+        Ok(_) => panic!("Expected PayloadTooLarge"),
+    }
+}
+
+#[test]
+fn binary_encoding_round_trip() {
+    let tangle = InMemoryTransport::new();
+    let (mut author, mut subscriber) = connected_channel(&tangle);
+
+    let signed_tag = author
+        .write_signed(
+            true,
+            PayloadBuilder::with_encoding(PayloadEncoding::Binary)
+                .masked(&SensorData::new(3, 5.0))
+                .unwrap()
+                .build(),
+        )
+        .unwrap();
+
+    let message = subscriber
+        .read_signed_as::<JsonSerializer, SensorData, SensorData>(signed_tag)
+        .unwrap()
+        .remove(0)
+        .unwrap();
+    assert_eq!(message.public, None);
+    assert_eq!(message.masked, Some(SensorData::new(3, 5.0)));
+}
+
+#[test]
+fn envelope_round_trip() {
+    let tangle = InMemoryTransport::new();
+    let (mut author, mut subscriber) = connected_channel(&tangle);
+
+    let tagged_tag = author
+        .write_tagged(
+            PayloadBuilder::with_schema(Schema::new("sensor-data", 2))
+                .public(&SensorData::new(1, 1.0))
+                .unwrap()
+                .masked(&SensorData::new(1, 2.0))
+                .unwrap()
+                .build(),
+        )
+        .unwrap();
+
+    let message = subscriber
+        .read_tagged_envelopes(tagged_tag.clone())
+        .unwrap()
+        .remove(0)
+        .unwrap();
+    let envelope = message.masked.unwrap();
+    assert_eq!(envelope.header.content_type, ContentType::Json);
+    assert_eq!(envelope.header.schema, Some(Schema::new("sensor-data", 2)));
+    assert!(envelope.header.created.is_some());
+    assert_eq!(envelope.header.compression, None);
+    assert_eq!(
+        envelope.decode::<SensorData>().unwrap(),
+        SensorData::new(1, 2.0)
+    );
+    assert_eq!(
+        envelope
+            .decode_with::<JsonSerializer, SensorData>()
+            .unwrap(),
+        SensorData::new(1, 2.0)
+    );
+
+    // The typed readers open the envelopes too
+    let message = subscriber
+        .read_tagged_as::<JsonSerializer, SensorData, SensorData>(tagged_tag)
+        .unwrap()
+        .remove(0)
+        .unwrap();
+    assert_eq!(message.public, Some(SensorData::new(1, 1.0)));
+}
+
+#[cfg(any(feature = "deflate", feature = "zstd"))]
+fn telemetry() -> Vec<SensorData> {
+    (0..50).map(|ts| SensorData::new(ts, 1.0)).collect()
+}
+
+#[cfg(any(feature = "deflate", feature = "zstd"))]
+fn compressed_round_trip(compression: Compression) {
+    let tangle = InMemoryTransport::new();
+    let (mut author, mut subscriber) = connected_channel(&tangle);
+
+    let mut uncompressed = PayloadBuilder::with_encoding(PayloadEncoding::Envelope);
+    uncompressed.masked(&telemetry()).unwrap();
+    let mut builder = PayloadBuilder::new().compressed(compression).unwrap();
+    builder.masked(&telemetry()).unwrap();
+    assert!(builder.encoded_size() < uncompressed.encoded_size());
+
+    let tagged_tag = author.write_tagged(builder.build()).unwrap();
+    let message = subscriber
+        .read_tagged_envelopes(tagged_tag.clone())
+        .unwrap()
+        .remove(0)
+        .unwrap();
+    let envelope = message.masked.unwrap();
+    assert_eq!(envelope.header.compression, Some(compression));
+    assert_eq!(envelope.decode::<Vec<SensorData>>().unwrap(), telemetry());
+
+    let message = subscriber
+        .read_tagged_as::<JsonSerializer, Vec<SensorData>, Vec<SensorData>>(tagged_tag)
+        .unwrap()
+        .remove(0)
+        .unwrap();
+    assert_eq!(message.masked, Some(telemetry()));
+}
+
+#[cfg(feature = "deflate")]
+#[test]
+fn deflate_round_trip() {
+    compressed_round_trip(Compression::Deflate);
+}
+
+#[cfg(feature = "zstd")]
+#[test]
+fn zstd_round_trip() {
+    compressed_round_trip(Compression::Zstd);
+}
+
+#[cfg(feature = "deflate")]
+#[test]
+fn decompression_is_bounded() {
+    let data = vec![0u8; 4096];
+    let compressed = Compression::Deflate.compress(&data).unwrap();
+
+    assert_eq!(
+        Compression::Deflate.decompress(&compressed, 4096).unwrap(),
+        data
+    );
+    match Compression::Deflate.decompress(&compressed, 1024) {
+        Err(ChannelError::MalformedPayload(_)) => {}
+        other => panic!("Expected MalformedPayload, got {:?}", other),
+    }
+}
+
+#[cfg(not(feature = "zstd"))]
+#[test]
+fn disabled_compression_is_reported() {
+    match Compression::Zstd.compress(b"data") {
+        Err(ChannelError::MalformedPayload(_)) => {}
+        other => panic!("Expected MalformedPayload, got {:?}", other),
+    }
+}
+
+#[test]
+fn compression_must_precede_the_data() {
+    let mut builder = PayloadBuilder::new();
+    builder.public(&SensorData::new(1, 1.0)).unwrap();
+    match builder.compressed(Compression::Deflate) {
+        Err(ChannelError::MalformedPayload(_)) => {}
+        Err(e) => panic!("Expected MalformedPayload, got {}", e),
+        Ok(_) => panic!("Expected MalformedPayload"),
+    }
+}
+
+#[test]
+fn chunked_blob_round_trip() {
+    let tangle = InMemoryTransport::new();
+    let (mut author, mut subscriber) = connected_channel(&tangle);
+    let blob = blob(4096);
+
+    let mut chunk_tags = author.write_tagged_chunked(&blob).unwrap();
+    assert!(chunk_tags.len() > 1);
+    chunk_tags.reverse();
+    assert_eq!(subscriber.read_chunked(&chunk_tags).unwrap(), blob);
+
+    let chunk_tags = author.write_signed_chunked(true, &blob).unwrap();
+    assert_eq!(subscriber.read_chunked(&chunk_tags).unwrap(), blob);
+
+    match subscriber.read_chunked(&chunk_tags[1..]) {
+        Err(ChannelError::MessageNotFound(_)) => {}
+        other => panic!("Expected MessageNotFound, got {:?}", other),
+    }
+}
+
+#[test]
+fn empty_blob_round_trip() {
+    let tangle = InMemoryTransport::new();
+    let (mut author, mut subscriber) = connected_channel(&tangle);
+
+    let chunk_tags = author.write_tagged_chunked(&[]).unwrap();
+    assert_eq!(chunk_tags.len(), 1);
+    assert_eq!(
+        subscriber.read_chunked(&chunk_tags).unwrap(),
+        Vec::<u8>::new()
+    );
+}
+
+#[test]
+fn partly_written_blob_reports_the_chunks_sent() {
+    // Room for the announcement, the keyload and two chunks
+    let tangle = InMemoryTransport::new();
+    let transport = LimitedTransport {
+        tangle: tangle.clone(),
+        sends_left: 4,
+    };
+    let mut author = Author::with_transport(transport, None).unwrap();
+    author.set_retry_policy(RetryPolicy::none());
+    let (address, announcement_tag) = author.open().unwrap();
+    let mut subscriber =
+        Subscriber::with_transport(tangle.clone(), address, announcement_tag, None).unwrap();
+    let subscription_tag = subscriber.connect().unwrap();
+    let keyload_tag = author.add_subscriber(subscription_tag).unwrap();
+    subscriber.update_keyload(keyload_tag).unwrap();
+
+    let tags = match author.write_tagged_chunked(&blob(4 * MAX_PAYLOAD_SIZE)) {
+        Err(ChannelError::PartialWrite { tags, reason }) => {
+            assert!(matches!(*reason, ChannelError::Transport(_)));
+            tags
+        }
+        other => panic!("Expected PartialWrite, got {:?}", other),
+    };
+    assert_eq!(tags.len(), 2);
+    match subscriber.read_chunked(&tags) {
+        Err(ChannelError::MessageNotFound(_)) => {}
+        other => panic!("Expected MessageNotFound, got {:?}", other),
+    }
+
+    // Nothing sent, the error is returned as is
+    match author.write_tagged_chunked(&blob(16)) {
+        Err(ChannelError::Transport(_)) => {}
+        other => panic!("Expected Transport, got {:?}", other),
+    }
+}
@@ -0,0 +1,87 @@
+#![cfg(feature = "protobuf")]
+
+mod common;
+
+use channels_lite::channels::transport::InMemoryTransport;
+use channels_lite::utils::payload::envelope::ContentType;
+use channels_lite::utils::payload::protobuf::{PayloadBuilder, ProtobufSerializer};
+use channels_lite::utils::payload::PayloadEncoding;
+use common::connected_channel;
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct Reading {
+    #[prost(uint64, tag = "1")]
+    pub ts: u64,
+    #[prost(float, tag = "2")]
+    pub presure: f32,
+}
+
+fn reading(ts: u64, presure: f32) -> Reading {
+    Reading {
+        ts: ts,
+        presure: presure,
+    }
+}
+
+#[test]
+fn raw_protobuf_round_trip() {
+    let tangle = InMemoryTransport::new();
+    let (mut author, mut subscriber) = connected_channel(&tangle);
+
+    let tagged_tag = author
+        .write_tagged(
+            PayloadBuilder::with_encoding(PayloadEncoding::Binary)
+                .public(&reading(1, 1.0))
+                .unwrap()
+                .masked(&reading(1, 2.0))
+                .unwrap()
+                .build(),
+        )
+        .unwrap();
+
+    let message = subscriber
+        .read_tagged_as::<ProtobufSerializer, Reading, Reading>(tagged_tag)
+        .unwrap()
+        .remove(0)
+        .unwrap();
+    assert_eq!(message.public, Some(reading(1, 1.0)));
+    assert_eq!(message.masked, Some(reading(1, 2.0)));
+}
+
+#[test]
+fn enveloped_protobuf_round_trip() {
+    let tangle = InMemoryTransport::new();
+    let (mut author, mut subscriber) = connected_channel(&tangle);
+
+    let tagged_tag = author
+        .write_tagged(
+            PayloadBuilder::with_encoding(PayloadEncoding::Envelope)
+                .masked(&reading(2, 3.0))
+                .unwrap()
+                .build(),
+        )
+        .unwrap();
+
+    let envelope = subscriber
+        .read_tagged_envelopes(tagged_tag.clone())
+        .unwrap()
+        .remove(0)
+        .unwrap()
+        .masked
+        .unwrap();
+    assert_eq!(envelope.header.content_type, ContentType::Protobuf);
+    assert_eq!(
+        envelope
+            .decode_with::<ProtobufSerializer, Reading>()
+            .unwrap(),
+        reading(2, 3.0)
+    );
+
+    let message = subscriber
+        .read_tagged_as::<ProtobufSerializer, Reading, Reading>(tagged_tag)
+        .unwrap()
+        .remove(0)
+        .unwrap();
+    assert_eq!(message.public, None);
+    assert_eq!(message.masked, Some(reading(2, 3.0)));
+}
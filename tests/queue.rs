@@ -0,0 +1,146 @@
+mod common;
+
+use channels_lite::channels::channel_author::Channel as Author;
+use channels_lite::channels::channel_subscriber::Channel as Subscriber;
+use channels_lite::channels::transport::InMemoryTransport;
+use channels_lite::channels::{ChannelError, RetryPolicy};
+use common::{connected_channel, payload, FlakyTransport};
+use std::path::{Path, PathBuf};
+use std::thread;
+use std::time::Duration;
+
+const PASSWORD: &str = "password";
+
+///
+/// Queue file of the test, removed if left by a previous run
+///
+fn queue_path(name: &str) -> PathBuf {
+    let path = std::env::temp_dir().join(format!(
+        "channels_lite_{}_{}.json",
+        name,
+        std::process::id()
+    ));
+    let _ = std::fs::remove_file(&path);
+    path
+}
+
+///
+/// Send the messages of the author through a queue, offline for now
+///
+fn enable_offline_queue(
+    tangle: &InMemoryTransport,
+    author: &mut Author<InMemoryTransport>,
+    path: &Path,
+) -> FlakyTransport {
+    author.set_retry_policy(RetryPolicy {
+        initial_backoff_ms: 10,
+        max_backoff_ms: 50,
+        ..RetryPolicy::default()
+    });
+    let transport = FlakyTransport::new(tangle, false);
+    author
+        .enable_outbound_queue(path, transport.clone())
+        .unwrap();
+    transport
+}
+
+fn fetch_tags(subscriber: &mut Subscriber<InMemoryTransport>) -> Vec<String> {
+    subscriber
+        .fetch_next_messages()
+        .unwrap()
+        .into_iter()
+        .map(|msg| msg.unwrap().msgid)
+        .collect()
+}
+
+#[test]
+fn queued_messages_are_sent_in_order() {
+    let tangle = InMemoryTransport::new();
+    let (mut author, mut subscriber) = connected_channel(&tangle);
+    let path = queue_path("in_order");
+    let transport = enable_offline_queue(&tangle, &mut author, &path);
+
+    let handles: Vec<_> = (1..=3)
+        .map(|ts| author.enqueue_tagged(payload(ts)).unwrap())
+        .collect();
+    let tags: Vec<String> = handles.iter().map(|h| h.tag().to_string()).collect();
+    thread::sleep(Duration::from_millis(50));
+    assert_eq!(author.pending_messages(), 3);
+    assert!(fetch_tags(&mut subscriber).is_empty());
+
+    transport.set_online(true);
+    let sent: Vec<String> = handles.into_iter().map(|h| h.wait().unwrap()).collect();
+    assert_eq!(sent, tags);
+    assert_eq!(author.pending_messages(), 0);
+    assert_eq!(fetch_tags(&mut subscriber), tags);
+}
+
+#[test]
+fn queue_is_resumed_after_a_restart() {
+    let tangle = InMemoryTransport::new();
+    let (mut author, mut subscriber) = connected_channel(&tangle);
+    let bytes = author.export_state(PASSWORD).unwrap();
+    let path = queue_path("resumed");
+    enable_offline_queue(&tangle, &mut author, &path);
+
+    // Enqueued after the export, only the queue file knows about them
+    let mut tags: Vec<String> = (1..=2)
+        .map(|ts| {
+            author
+                .enqueue_tagged(payload(ts))
+                .unwrap()
+                .tag()
+                .to_string()
+        })
+        .collect();
+    drop(author);
+    assert!(fetch_tags(&mut subscriber).is_empty());
+
+    let mut author = Author::import_state_with_queue(
+        tangle.clone(),
+        &bytes,
+        PASSWORD,
+        &path,
+        FlakyTransport::new(&tangle, true),
+    )
+    .unwrap();
+    author.reshare_keyloads().unwrap();
+    let handle = author.enqueue_tagged(payload(3)).unwrap();
+    tags.push(handle.wait().unwrap());
+    assert_eq!(author.pending_messages(), 0);
+    assert_eq!(fetch_tags(&mut subscriber), tags);
+}
+
+#[test]
+fn queue_of_a_newer_state_is_refused() {
+    let tangle = InMemoryTransport::new();
+    let (mut author, _subscriber) = connected_channel(&tangle);
+    let bytes = author.export_state(PASSWORD).unwrap();
+    let path = queue_path("newer");
+    enable_offline_queue(&tangle, &mut author, &path);
+    author.enqueue_tagged(payload(1)).unwrap();
+    drop(author);
+
+    let mut author = Author::import_state_with_transport(tangle.clone(), &bytes, PASSWORD).unwrap();
+    match author.enable_outbound_queue(&path, FlakyTransport::new(&tangle, true)) {
+        Err(ChannelError::InvalidState(_)) => {}
+        Err(e) => panic!("Expected InvalidState, got {:?}", e),
+        Ok(()) => panic!("Expected InvalidState"),
+    }
+}
+
+#[test]
+fn queue_of_another_channel_is_refused() {
+    let tangle = InMemoryTransport::new();
+    let (mut author, _subscriber) = connected_channel(&tangle);
+    let path = queue_path("other_channel");
+    enable_offline_queue(&tangle, &mut author, &path);
+    drop(author);
+
+    let (mut other, _subscriber) = connected_channel(&tangle);
+    match other.enable_outbound_queue(&path, FlakyTransport::new(&tangle, true)) {
+        Err(ChannelError::Queue(_)) => {}
+        Err(e) => panic!("Expected Queue, got {:?}", e),
+        Ok(()) => panic!("Expected Queue"),
+    }
+}
@@ -0,0 +1,60 @@
+mod common;
+
+use channels_lite::channels::channel_author::Channel as Author;
+use channels_lite::channels::channel_subscriber::MessageType;
+use channels_lite::channels::transport::InMemoryTransport;
+use common::{connect, payload};
+
+#[test]
+fn reads_return_the_message_metadata() {
+    let tangle = InMemoryTransport::new();
+    let mut author = Author::with_transport(tangle.clone(), None).unwrap();
+    let (address, announcement_tag) = author.open().unwrap();
+    let mut subscriber = connect(&tangle, &mut author, &address, &announcement_tag);
+    let keyload_tag = author.keyload_tag().unwrap();
+
+    let signed_tag = author.write_signed(false, payload(1)).unwrap();
+    let masked_tag = author.write_signed(true, payload(2)).unwrap();
+    let tagged_tag = author.write_tagged(payload(3)).unwrap();
+
+    let signed = subscriber
+        .read_signed(signed_tag.clone())
+        .unwrap()
+        .remove(0)
+        .unwrap();
+    assert_eq!(signed.msgid, signed_tag);
+    assert_eq!(signed.message_type, MessageType::Signed);
+    assert!(signed.signer.is_some());
+    assert_eq!(signed.linked_to, announcement_tag);
+    assert!(signed.timestamp.unwrap() > 0);
+
+    let masked = subscriber
+        .read_signed(masked_tag)
+        .unwrap()
+        .remove(0)
+        .unwrap();
+    assert_eq!(masked.linked_to, keyload_tag);
+    assert_eq!(masked.signer, signed.signer);
+
+    let tagged = subscriber
+        .read_tagged(tagged_tag.clone())
+        .unwrap()
+        .remove(0)
+        .unwrap();
+    assert_eq!(tagged.msgid, tagged_tag);
+    assert_eq!(tagged.message_type, MessageType::Tagged);
+    assert_eq!(tagged.signer, None);
+    assert_eq!(tagged.linked_to, keyload_tag);
+    assert!(tagged.timestamp >= signed.timestamp);
+}
+
+#[test]
+fn reads_filter_the_message_type() {
+    let tangle = InMemoryTransport::new();
+    let mut author = Author::with_transport(tangle.clone(), None).unwrap();
+    let (address, announcement_tag) = author.open().unwrap();
+    let mut subscriber = connect(&tangle, &mut author, &address, &announcement_tag);
+    let tagged_tag = author.write_tagged(payload(1)).unwrap();
+
+    assert!(subscriber.read_signed(tagged_tag).unwrap().is_empty());
+}
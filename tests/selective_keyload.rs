@@ -0,0 +1,64 @@
+mod common;
+
+use channels_lite::channels::channel_author::Channel as Author;
+use channels_lite::channels::transport::InMemoryTransport;
+use channels_lite::channels::ChannelError;
+use common::{connect, payload, read_masked, SensorData};
+
+#[test]
+fn revoke_shares_selective_keyloads_again() {
+    let tangle = InMemoryTransport::new();
+    let mut author = Author::with_transport(tangle.clone(), None).unwrap();
+    let (address, announcement_tag) = author.open().unwrap();
+    let mut crew = connect(&tangle, &mut author, &address, &announcement_tag);
+    let _customer = connect(&tangle, &mut author, &address, &announcement_tag);
+    let subscribers = author.subscribers();
+
+    let selective_tag = author.share_keyload_for(&subscribers).unwrap();
+    let keyloads = author.revoke_subscriber(&subscribers[1]).unwrap();
+    assert_eq!(keyloads.selective_keyloads.len(), 1);
+    let (old_tag, new_tag) = keyloads.selective_keyloads[0].clone();
+    assert_eq!(old_tag, selective_tag);
+    let new_tag = new_tag.unwrap();
+    assert_eq!(author.keyload_subscribers(&old_tag), None);
+    assert_eq!(
+        author.keyload_subscribers(&new_tag),
+        Some(vec![subscribers[0].clone()])
+    );
+
+    crew.update_keyload(new_tag.clone()).unwrap();
+    let signed_tag = author
+        .write_signed_with_keyload(&new_tag, payload(4))
+        .unwrap();
+    assert_eq!(read_masked(&mut crew, signed_tag), SensorData::new(4, 2.0));
+}
+
+#[test]
+fn subscribers_listed_twice_are_kept_once() {
+    let tangle = InMemoryTransport::new();
+    let mut author = Author::with_transport(tangle.clone(), None).unwrap();
+    let (address, announcement_tag) = author.open().unwrap();
+    let _crew = connect(&tangle, &mut author, &address, &announcement_tag);
+    let crew_id = author.subscribers()[0].clone();
+
+    let keyload_tag = author
+        .share_keyload_for(&[crew_id.clone(), crew_id.clone()])
+        .unwrap();
+    assert_eq!(
+        author.keyload_subscribers(&keyload_tag),
+        Some(vec![crew_id])
+    );
+}
+
+#[test]
+fn keyload_for_no_subscriber_is_refused() {
+    let tangle = InMemoryTransport::new();
+    let mut author = Author::with_transport(tangle.clone(), None).unwrap();
+    author.open().unwrap();
+
+    match author.share_keyload_for(&[]) {
+        Err(ChannelError::NoSubscriber) => {}
+        other => panic!("Expected NoSubscriber, got {:?}", other),
+    }
+    assert_eq!(tangle.len(), 1);
+}
@@ -0,0 +1,57 @@
+#![cfg(any(feature = "cbor", feature = "msgpack", feature = "bincode"))]
+
+mod common;
+
+use channels_lite::channels::transport::InMemoryTransport;
+use channels_lite::utils::payload::envelope::PayloadFormat;
+use channels_lite::utils::payload::{PayloadBuilder, PayloadDeserializer, PayloadSerializer};
+use common::{connected_channel, SensorData};
+
+///
+/// Write a signed packet serialized with `S` and read it back
+///
+fn round_trip<S>()
+where
+    S: PayloadSerializer<SensorData> + PayloadDeserializer<SensorData> + PayloadFormat,
+{
+    let tangle = InMemoryTransport::new();
+    let (mut author, mut subscriber) = connected_channel(&tangle);
+
+    let signed_tag = author
+        .write_signed(
+            true,
+            PayloadBuilder::<S>::new()
+                .public(&SensorData::new(1, 1.0))
+                .unwrap()
+                .masked(&SensorData::new(1, 2.0))
+                .unwrap()
+                .build(),
+        )
+        .unwrap();
+
+    let message = subscriber
+        .read_signed_as::<S, SensorData, SensorData>(signed_tag)
+        .unwrap()
+        .remove(0)
+        .unwrap();
+    assert_eq!(message.public, Some(SensorData::new(1, 1.0)));
+    assert_eq!(message.masked, Some(SensorData::new(1, 2.0)));
+}
+
+#[cfg(feature = "cbor")]
+#[test]
+fn cbor_round_trip() {
+    round_trip::<channels_lite::utils::payload::cbor::CborSerializer>();
+}
+
+#[cfg(feature = "msgpack")]
+#[test]
+fn msgpack_round_trip() {
+    round_trip::<channels_lite::utils::payload::msgpack::MsgPackSerializer>();
+}
+
+#[cfg(feature = "bincode")]
+#[test]
+fn bincode_round_trip() {
+    round_trip::<channels_lite::utils::payload::bincode::BincodeSerializer>();
+}
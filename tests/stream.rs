@@ -0,0 +1,26 @@
+mod common;
+
+use channels_lite::channels::channel_subscriber::AsyncChannel;
+use channels_lite::channels::transport::InMemoryTransport;
+use common::{connected_channel, payload};
+use futures::StreamExt;
+use std::time::Duration;
+
+#[tokio::test]
+async fn stream_yields_the_messages_until_disconnected() {
+    let tangle = InMemoryTransport::new();
+    let (mut author, subscriber) = connected_channel(&tangle);
+    let signed_tag = author.write_signed(false, payload(1)).unwrap();
+    let tagged_tag = author.write_tagged(payload(2)).unwrap();
+
+    let subscriber: AsyncChannel<InMemoryTransport> = subscriber.into();
+    let mut messages = Box::pin(subscriber.subscribe_stream(Duration::from_millis(10)));
+    let mut tags = vec![];
+    for _ in 0..2 {
+        tags.push(messages.next().await.unwrap().unwrap().msgid);
+    }
+    assert_eq!(tags, vec![signed_tag, tagged_tag]);
+
+    subscriber.disconnect().await.unwrap();
+    assert!(messages.next().await.is_none());
+}
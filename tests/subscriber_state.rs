@@ -0,0 +1,34 @@
+mod common;
+
+use channels_lite::channels::channel_subscriber::Channel as Subscriber;
+use channels_lite::channels::transport::InMemoryTransport;
+use common::{connected_channel, payload, read_masked, FlakyTransport};
+
+#[test]
+fn subscriber_state_is_restored_without_the_network() {
+    let tangle = InMemoryTransport::new();
+    let (mut author, subscriber) = connected_channel(&tangle);
+    let snapshot = subscriber.export_state("password").unwrap();
+    drop(subscriber);
+
+    let offline = FlakyTransport::new(&tangle, false);
+    let messages = tangle.len();
+    let mut subscriber =
+        Subscriber::import_state_with_transport(offline.clone(), &snapshot, "password").unwrap();
+    assert_eq!(tangle.len(), messages);
+
+    // The keyload is restored, no need to fetch it again
+    let signed_tag = author.write_signed(true, payload(9)).unwrap();
+    offline.set_online(true);
+    assert_eq!(read_masked(&mut subscriber, signed_tag).ts, 9);
+}
+
+#[test]
+fn subscriber_state_requires_the_password() {
+    let tangle = InMemoryTransport::new();
+    let (_author, subscriber) = connected_channel(&tangle);
+    let snapshot = subscriber.export_state("password").unwrap();
+
+    assert!(Subscriber::import_state_with_transport(tangle.clone(), &snapshot, "wrong").is_err());
+    assert!(Subscriber::import_state_with_transport(tangle, &snapshot[1..], "password").is_err());
+}
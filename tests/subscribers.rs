@@ -0,0 +1,84 @@
+mod common;
+
+use channels_lite::channels::channel_author::Channel as Author;
+use channels_lite::channels::transport::InMemoryTransport;
+use channels_lite::channels::ChannelError;
+use common::{connect, payload, read_masked};
+
+#[test]
+fn unsubscribed_subscriber_is_left_out_of_the_new_keyload() {
+    let tangle = InMemoryTransport::new();
+    let mut author = Author::with_transport(tangle.clone(), None).unwrap();
+    let (address, announcement_tag) = author.open().unwrap();
+    let mut staying = connect(&tangle, &mut author, &address, &announcement_tag);
+    let mut leaving = connect(&tangle, &mut author, &address, &announcement_tag);
+    assert_eq!(author.subscribers().len(), 2);
+
+    let unsubscribe_tag = leaving.disconnect().unwrap();
+    let keyloads = author.remove_subscriber(unsubscribe_tag).unwrap();
+    assert_eq!(author.keyload_tag(), Some(keyloads.keyload_tag.clone()));
+    assert_eq!(author.subscribers().len(), 1);
+    assert!(keyloads.selective_keyloads.is_empty());
+
+    staying.update_keyload(keyloads.keyload_tag).unwrap();
+    let signed_tag = author.write_signed(true, payload(3)).unwrap();
+    assert_eq!(read_masked(&mut staying, signed_tag).ts, 3);
+}
+
+#[test]
+fn revoked_subscriber_is_unknown() {
+    let tangle = InMemoryTransport::new();
+    let mut author = Author::with_transport(tangle.clone(), None).unwrap();
+    let (address, announcement_tag) = author.open().unwrap();
+    let _subscriber = connect(&tangle, &mut author, &address, &announcement_tag);
+    let subscriber_id = author.subscribers().remove(0);
+
+    author.revoke_subscriber(&subscriber_id).unwrap();
+    assert!(author.subscribers().is_empty());
+    match author.revoke_subscriber(&subscriber_id) {
+        Err(ChannelError::UnknownSubscriber(_)) => {}
+        other => panic!("Expected UnknownSubscriber, got {:?}", other),
+    }
+}
+
+#[test]
+fn revoke_is_kept_across_export() {
+    let tangle = InMemoryTransport::new();
+    let mut author = Author::with_transport(tangle.clone(), None).unwrap();
+    let (address, announcement_tag) = author.open().unwrap();
+    let mut staying = connect(&tangle, &mut author, &address, &announcement_tag);
+    let _leaving = connect(&tangle, &mut author, &address, &announcement_tag);
+    let leaving_id = author.subscribers()[1].clone();
+    author.revoke_subscriber(&leaving_id).unwrap();
+
+    let snapshot = author.export_state("password").unwrap();
+    let mut author =
+        Author::import_state_with_transport(tangle.clone(), &snapshot, "password").unwrap();
+    assert!(!author.subscribers().contains(&leaving_id));
+
+    let keyloads = author.reshare_keyloads().unwrap();
+    staying.update_keyload(keyloads.keyload_tag).unwrap();
+    let signed_tag = author.write_signed(true, payload(7)).unwrap();
+    assert_eq!(read_masked(&mut staying, signed_tag).ts, 7);
+}
+
+#[test]
+fn disconnected_subscriber_can_not_read() {
+    let tangle = InMemoryTransport::new();
+    let mut author = Author::with_transport(tangle.clone(), None).unwrap();
+    let (address, announcement_tag) = author.open().unwrap();
+    let mut subscriber = connect(&tangle, &mut author, &address, &announcement_tag);
+    let signed_tag = author.write_signed(true, payload(1)).unwrap();
+
+    let unsubscribe_tag = subscriber.disconnect().unwrap();
+    assert_eq!(tangle.len(), 5);
+    assert_ne!(unsubscribe_tag, signed_tag);
+    match subscriber.read_signed(signed_tag) {
+        Err(ChannelError::NotConnected) => {}
+        other => panic!("Expected NotConnected, got {:?}", other),
+    }
+    match subscriber.disconnect() {
+        Err(ChannelError::NotConnected) => {}
+        other => panic!("Expected NotConnected, got {:?}", other),
+    }
+}
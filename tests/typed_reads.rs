@@ -0,0 +1,77 @@
+mod common;
+
+use channels_lite::channels::transport::InMemoryTransport;
+use channels_lite::channels::ChannelError;
+use channels_lite::utils::payload::json::{JsonSerializer, PayloadBuilder};
+use common::{connected_channel, payload, SensorData};
+
+#[test]
+fn typed_reads_deserialize_the_data() {
+    let tangle = InMemoryTransport::new();
+    let (mut author, mut subscriber) = connected_channel(&tangle);
+    let tagged_tag = author.write_tagged(payload(1)).unwrap();
+
+    let messages = subscriber
+        .read_tagged_as::<JsonSerializer, SensorData, SensorData>(tagged_tag.clone())
+        .unwrap();
+    assert_eq!(messages.len(), 1);
+    let message = messages[0].as_ref().unwrap();
+    assert_eq!(message.public, Some(SensorData::new(1, 1.0)));
+    assert_eq!(message.masked, Some(SensorData::new(1, 2.0)));
+
+    // The untyped reads return the JSON text
+    let message = subscriber
+        .read_tagged(tagged_tag)
+        .unwrap()
+        .remove(0)
+        .unwrap();
+    let public: SensorData = serde_json::from_str(&message.public.unwrap()).unwrap();
+    assert_eq!(public, SensorData::new(1, 1.0));
+}
+
+#[test]
+fn decode_failures_are_reported_per_message() {
+    let tangle = InMemoryTransport::new();
+    let (mut author, mut subscriber) = connected_channel(&tangle);
+    let signed_tag = author
+        .write_signed(
+            false,
+            PayloadBuilder::new()
+                .public(&"not a sensor")
+                .unwrap()
+                .build(),
+        )
+        .unwrap();
+
+    let messages = subscriber
+        .read_signed_as::<JsonSerializer, SensorData, SensorData>(signed_tag)
+        .unwrap();
+    assert_eq!(messages.len(), 1);
+    match &messages[0] {
+        Err(ChannelError::MalformedPayload(_)) => {}
+        other => panic!("Expected MalformedPayload, got {:?}", other),
+    }
+}
+
+#[test]
+fn empty_data_is_none() {
+    let tangle = InMemoryTransport::new();
+    let (mut author, mut subscriber) = connected_channel(&tangle);
+    let signed_tag = author
+        .write_signed(
+            false,
+            PayloadBuilder::new()
+                .public(&SensorData::new(2, 1.0))
+                .unwrap()
+                .build(),
+        )
+        .unwrap();
+
+    let message = subscriber
+        .read_signed_as::<JsonSerializer, SensorData, SensorData>(signed_tag)
+        .unwrap()
+        .remove(0)
+        .unwrap();
+    assert_eq!(message.public, Some(SensorData::new(2, 1.0)));
+    assert_eq!(message.masked, None);
+}